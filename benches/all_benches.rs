@@ -1,4 +1,5 @@
-use aoc_2021::day03;
+use aoc_2021::xorshift::XorShift;
+use aoc_2021::{day01, day02, day03};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
 const BENCH_INPUT: &str = "101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n010101010101\n101010101010\n";
@@ -8,18 +9,20 @@ fn bench_day03_add_lines(c: &mut Criterion) {
     let short_input = BENCH_INPUT;
     let medium_input = &BENCH_INPUT.repeat(101);
     let long_input = &BENCH_INPUT.repeat(1001);
+    let huge_input = &BENCH_INPUT.repeat(10001);
 
     let mut group = c.benchmark_group("day03 add_lines");
-    for &i in [27, 2727, 27027].iter() {
+    for &i in [27, 2727, 27027, 270027].iter() {
         group.bench_with_input(
             BenchmarkId::new("simd", i),
             match i {
                 27 => short_input,
                 2727 => medium_input,
                 27027 => long_input,
+                270027 => huge_input,
                 _ => panic!(),
             },
-            |b, input| b.iter(|| day03::add_lines_simd(black_box(input), black_box(i))),
+            |b, input| b.iter(|| day03::add_lines_simd(black_box(input), black_box(i), 12)),
         );
         group.bench_with_input(
             BenchmarkId::new("naive", i),
@@ -27,13 +30,147 @@ fn bench_day03_add_lines(c: &mut Criterion) {
                 27 => short_input,
                 2727 => medium_input,
                 27027 => long_input,
+                270027 => huge_input,
                 _ => panic!(),
             },
-            |b, input| b.iter(|| day03::add_lines_naive(black_box(input), black_box(i))),
+            |b, input| b.iter(|| day03::add_lines_naive(black_box(input), black_box(i), 12)),
         );
+        group.bench_with_input(
+            BenchmarkId::new("par", i),
+            match i {
+                27 => short_input,
+                2727 => medium_input,
+                27027 => long_input,
+                270027 => huge_input,
+                _ => panic!(),
+            },
+            |b, input| b.iter(|| day03::add_lines_par(black_box(input), black_box(i), 12)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_day03_kernels(c: &mut Criterion) {
+    let input = &BENCH_INPUT.repeat(10001);
+
+    let mut group = c.benchmark_group("day03 kernels");
+    for kernel in day03::Kernel::ALL.iter().copied() {
+        if !kernel.is_supported() {
+            continue;
+        }
+        group.bench_with_input(
+            BenchmarkId::new(kernel.name(), 270027),
+            input,
+            |b, input| {
+                b.iter(|| day03::add_lines_kernel(kernel, black_box(input), black_box(270027), 12))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_day03_part2(c: &mut Criterion) {
+    // part 2 needs distinct numbers (xorshift never repeats before its period)
+    let numbers: Vec<u64> = XorShift::new(0).take(1_000_000).collect();
+    let to_input = |count: usize| {
+        numbers[..count]
+            .iter()
+            .map(|n| format!("{:064b}\n", n))
+            .collect::<String>()
+    };
+
+    let mut group = c.benchmark_group("day03 part2");
+    group.sample_size(20);
+    for &i in [1_000, 100_000, 1_000_000].iter() {
+        let input = &to_input(i);
+        group.bench_with_input(BenchmarkId::new("sort", i), input, |b, input| {
+            b.iter(|| day03::step_two(black_box(input), 64))
+        });
+        group.bench_with_input(BenchmarkId::new("trie", i), input, |b, input| {
+            b.iter(|| day03::step_two_trie(black_box(input), 64))
+        });
+    }
+    group.finish();
+}
+
+fn bench_day01_count_increases(c: &mut Criterion) {
+    let long_input: Vec<u32> = XorShift::new(0)
+        .take(10_000_000)
+        .map(|n| (n % 10_000) as u32)
+        .collect();
+
+    let mut group = c.benchmark_group("day01 count_increases");
+    for &i in [2_000, 1_000_000, 10_000_000].iter() {
+        let input = &long_input[..i];
+        group.bench_with_input(BenchmarkId::new("scalar", i), input, |b, input| {
+            b.iter(|| day01::count_window_increases(black_box(input), black_box(3)))
+        });
+        group.bench_with_input(BenchmarkId::new("simd", i), input, |b, input| {
+            b.iter(|| day01::count_window_increases_simd(black_box(input), black_box(3)))
+        });
+        group.bench_with_input(BenchmarkId::new("par", i), input, |b, input| {
+            b.iter(|| day01::count_window_increases_par(black_box(input), black_box(3)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_day01_parse(c: &mut Criterion) {
+    let input = include_str!("../src/input/day01.txt");
+    let long_input = input.repeat(1000);
+
+    let mut group = c.benchmark_group("day01 parse");
+    for (name, input) in [("input", input), ("input x1000", long_input.as_str())] {
+        group.bench_with_input(BenchmarkId::new("naive", name), input, |b, input| {
+            b.iter(|| day01::parse_input_naive(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("swar", name), input, |b, input| {
+            b.iter(|| day01::parse_depths(black_box(input.as_bytes())))
+        });
+    }
+    group.finish();
+}
+
+fn bench_day02_part2(c: &mut Criterion) {
+    use day02::Direction;
+
+    let commands: Vec<day02::Command> = XorShift::new(0)
+        .take(8_000_000)
+        .map(|n| {
+            let dir = match n % 3 {
+                0 => Direction::Forward,
+                1 => Direction::Up,
+                _ => Direction::Down,
+            };
+            (dir, (n >> 8) as u32 % 10)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("day02 part2");
+    group.sample_size(20);
+    for &i in [1_000, 1_000_000, 8_000_000].iter() {
+        let input = &commands[..i];
+        group.bench_with_input(BenchmarkId::new("fold", i), input, |b, input| {
+            b.iter(|| day02::get_part2(black_box(input).iter(), true))
+        });
+        group.bench_with_input(BenchmarkId::new("par", i), input, |b, input| {
+            b.iter(|| day02::get_part2_par(black_box(input)))
+        });
     }
     group.finish();
 }
 
+criterion_group!(day01_parse, bench_day01_parse);
+criterion_group!(day02_part2, bench_day02_part2);
+criterion_group!(day01_count, bench_day01_count_increases);
 criterion_group!(day03_step1, bench_day03_add_lines);
-criterion_main!(day03_step1);
+criterion_group!(day03_kernels, bench_day03_kernels);
+criterion_group!(day03_part2, bench_day03_part2);
+criterion_main!(
+    day01_parse,
+    day01_count,
+    day02_part2,
+    day03_step1,
+    day03_kernels,
+    day03_part2
+);
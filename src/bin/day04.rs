@@ -1,4 +1,19 @@
+use aoc_2021::cache::{OrExit, ResultCache};
+use aoc_2021::check::{self, Problem};
+use aoc_2021::cli::{Args, Command};
+use aoc_2021::params::{Param, Params};
+use std::collections::HashSet;
+
 const INPUT: &str = include_str!("../input/day04.txt");
+/// bingo numbers go from 0 to 99
+const MAX_NUMBER: u8 = 99;
+const BOARD_SIZE: usize = 5;
+const PARAMS: &[Param] = &[Param {
+    name: "board_size",
+    default: BOARD_SIZE,
+    help: "number of rows and columns of each bingo board",
+    choices: &[],
+}];
 
 #[derive(Debug)]
 struct Board {
@@ -9,11 +24,12 @@ struct Board {
 impl Board {
     fn is_done(&self) -> bool {
         // any row done?
-        if (0..5).any(|row| self.is_row_complete(row)) {
+        if (0..self.marked.len()).any(|row| self.is_row_complete(row)) {
             return true;
         }
 
-        (0..5).any(|col| self.is_col_complete(col))
+        let cols = self.marked.first().map_or(0, |row| row.len());
+        (0..cols).any(|col| self.is_col_complete(col))
     }
 
     fn is_row_complete(&self, row_index: usize) -> bool {
@@ -66,12 +82,15 @@ struct Input {
     boards: Vec<Board>,
 }
 
-fn parse_input(input: &str) -> Input {
+fn parse_input(input: &str, board_size: usize) -> Input {
     let end_of_draw_line = input.find('\n').unwrap();
     let numbers_drawn = dbg!(parse_draw_list(&input[0..end_of_draw_line]));
 
     let remaining = &input[end_of_draw_line + 1..];
-    let boards = parse_boards(remaining.lines().collect::<Vec<_>>().chunks(6));
+    let boards = parse_boards(
+        remaining.lines().collect::<Vec<_>>().chunks(board_size + 1),
+        board_size,
+    );
 
     Input {
         numbers_drawn,
@@ -79,9 +98,9 @@ fn parse_input(input: &str) -> Input {
     }
 }
 use std::slice::Chunks;
-fn parse_boards(board_chunks: Chunks<&str>) -> Vec<Board> {
+fn parse_boards(board_chunks: Chunks<&str>, board_size: usize) -> Vec<Board> {
     board_chunks
-        .filter(|board| board.len() == 6)
+        .filter(|board| board.len() == board_size + 1)
         .map(|board| {
             let num = board
                 .iter()
@@ -95,7 +114,7 @@ fn parse_boards(board_chunks: Chunks<&str>) -> Vec<Board> {
 
             Board {
                 numbers: num,
-                marked: vec![vec![false; 5]; 5],
+                marked: vec![vec![false; board_size]; board_size],
             }
         })
         .collect()
@@ -108,11 +127,83 @@ fn parse_draw_list(input: &str) -> NumbersDrawn {
         .collect()
 }
 
-fn solve_part1(input: &str) -> usize {
+fn check_input(input: &str, board_size: usize) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut lines = input.trim_end().lines().enumerate();
+
+    match lines.next() {
+        None => problems.push(Problem::new(1, "input is empty")),
+        Some((_, draws)) => {
+            for (pos, num) in draws.split(',').enumerate() {
+                match num.trim().parse::<u8>() {
+                    Ok(n) if n <= MAX_NUMBER => {}
+                    _ => problems.push(Problem::new(
+                        1,
+                        format!(
+                            "draw #{} is not a number between 0 and {}: {:?}",
+                            pos + 1,
+                            MAX_NUMBER,
+                            num
+                        ),
+                    )),
+                }
+            }
+        }
+    }
+
+    let lines: Vec<(usize, &str)> = lines.collect();
+    for board in lines.chunks(board_size + 1) {
+        let (separator_idx, separator) = board[0];
+        if !separator.trim().is_empty() {
+            problems.push(Problem::new(
+                separator_idx + 1,
+                "expected an empty line before each board",
+            ));
+        }
+        if board.len() != board_size + 1 {
+            problems.push(Problem::new(
+                separator_idx + 1,
+                format!(
+                    "board has {} rows, expected {}",
+                    board.len() - 1,
+                    board_size
+                ),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        for &(idx, line) in board.iter().skip(1) {
+            let row: Vec<&str> = line.split_whitespace().collect();
+            if row.len() != board_size {
+                problems.push(Problem::new(
+                    idx + 1,
+                    format!("row has {} numbers, expected {}", row.len(), board_size),
+                ));
+            }
+            for num in row {
+                match num.parse::<u8>() {
+                    Ok(n) if !seen.insert(n) => problems.push(Problem::new(
+                        idx + 1,
+                        format!("{} appears more than once on this board", n),
+                    )),
+                    Ok(_) => {}
+                    Err(_) => problems.push(Problem::new(
+                        idx + 1,
+                        format!("not a board number: {:?}", num),
+                    )),
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+fn solve_part1(input: &str, board_size: usize) -> usize {
     let Input {
         numbers_drawn,
         mut boards,
-    } = parse_input(input);
+    } = parse_input(input, board_size);
 
     for num in numbers_drawn.iter() {
         for board in boards.iter_mut() {
@@ -130,11 +221,11 @@ fn solve_part1(input: &str) -> usize {
     panic!("no one won!");
 }
 
-fn solve_part2(input: &str) -> usize {
+fn solve_part2(input: &str, board_size: usize) -> usize {
     let Input {
         numbers_drawn,
         mut boards,
-    } = parse_input(input);
+    } = parse_input(input, board_size);
 
     dbg!(boards.len());
     dbg!(numbers_drawn.len());
@@ -168,8 +259,31 @@ fn solve_part2(input: &str) -> usize {
 }
 
 fn main() {
-    println!("part1: {}", solve_part1(INPUT));
-    println!("part2: {}", solve_part2(INPUT));
+    let args = Args::from_env(&[Command::Solve, Command::Check]);
+    let input = args.input(INPUT);
+    let params = Params::from_args("day04", PARAMS, &args);
+    let board_size = params.get("board_size");
+    if args.command == Command::Check {
+        std::process::exit(check::report(&check_input(&input, board_size)));
+    }
+
+    let cache = ResultCache::new("day04", args.cache)
+        .or_exit()
+        .with_variant(&params);
+
+    let res: usize = cache
+        .solve("part1", "mark", input.as_bytes(), || {
+            solve_part1(&input, board_size)
+        })
+        .or_exit();
+    println!("part1: {}", res);
+
+    let res: usize = cache
+        .solve("part2", "mark", input.as_bytes(), || {
+            solve_part2(&input, board_size)
+        })
+        .or_exit();
+    println!("part2: {}", res);
 }
 
 #[cfg(test)]
@@ -178,12 +292,25 @@ mod test {
 
     #[test]
     fn test_day04_part1() {
-        assert_eq!(67716, solve_part1(INPUT));
+        assert_eq!(67716, solve_part1(INPUT, BOARD_SIZE));
     }
 
     #[test]
     fn test_day04_part2() {
-        assert_eq!(1830, solve_part2(INPUT));
+        assert_eq!(1830, solve_part2(INPUT, BOARD_SIZE));
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert!(check_input(INPUT, BOARD_SIZE).is_empty());
+
+        let input =
+            "1,2,100,x\n\n1 2 3 4 5\n6 7 8 9 10\n11 12 13 14\n16 17 18 19 1\n21 22 23 24 25\n";
+        let problems = check_input(input, BOARD_SIZE);
+        assert_eq!(
+            vec![1, 1, 5, 6],
+            problems.iter().map(|p| p.line).collect::<Vec<_>>()
+        );
     }
 
     #[test]
@@ -1,5 +1,32 @@
+use aoc_2021::cache::{OrExit, ResultCache};
+use aoc_2021::check::{self, Problem};
+use aoc_2021::cli::{Args, Command};
+use aoc_2021::params::{Param, Params};
+
 const INPUT: &str = include_str!("../input/day06.txt");
 
+// everything below is evaluated by the compiler, a wrong answer is a build error
+const INPUT_AGE_GROUPS: [usize; 9] = parse_age_groups(INPUT.as_bytes());
+const PART1: usize = count_lanternfishs(simulate_age_groups(INPUT_AGE_GROUPS, 80));
+const PART2: usize = count_lanternfishs(simulate_age_groups(INPUT_AGE_GROUPS, 256));
+const _: () = assert!(PART1 == 350149);
+const _: () = assert!(PART2 == 1590327954513);
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "part1_days",
+        default: 80,
+        help: "days to simulate for part 1",
+        choices: &[],
+    },
+    Param {
+        name: "part2_days",
+        default: 256,
+        help: "days to simulate for part 2",
+        choices: &[],
+    },
+];
+
 fn parse_input(input: &str) -> Vec<usize> {
     input
         .trim_end()
@@ -8,6 +35,45 @@ fn parse_input(input: &str) -> Vec<usize> {
         .collect()
 }
 
+fn check_input(input: &str) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut lines = input.lines();
+    for (pos, age) in lines.next().unwrap_or("").split(',').enumerate() {
+        match age.parse::<usize>() {
+            Ok(age) if age <= 8 => {}
+            _ => problems.push(Problem::new(
+                1,
+                format!("fish #{} has no valid age (0 to 8): {:?}", pos + 1, age),
+            )),
+        }
+    }
+    for (idx, line) in lines.enumerate() {
+        if !line.trim().is_empty() {
+            problems.push(Problem::new(
+                idx + 2,
+                "unexpected content after the first line",
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Counts the fish per age straight from the input bytes, usable in const context.
+const fn parse_age_groups(input: &[u8]) -> [usize; 9] {
+    let mut age_groups = [0usize; 9];
+    let mut idx = 0;
+    while idx < input.len() {
+        match input[idx] {
+            b'0'..=b'8' => age_groups[(input[idx] - b'0') as usize] += 1,
+            b',' | b'\n' | b'\r' | b' ' => {}
+            _ => panic!("invalid lanternfish age"),
+        }
+        idx += 1;
+    }
+
+    age_groups
+}
 
 fn simulate_lanternfishs(numbers: &[usize], days: usize) -> usize {
     let mut age_groups = [0usize; 9];
@@ -20,17 +86,34 @@ fn simulate_lanternfishs(numbers: &[usize], days: usize) -> usize {
 
     //print_age_group(&age_groups);
 
-    // then, for each day we want to simulate
-    for _ in 0..days {
+    // then, simulate every day and add up all currently existing lanternfish
+    count_lanternfishs(simulate_age_groups(age_groups, days))
+}
+
+const fn simulate_age_groups(mut age_groups: [usize; 9], days: usize) -> [usize; 9] {
+    let mut day = 0;
+    while day < days {
         // we shift the array to the left (lanternfish in group 1 move to group 0),
         //  and simulate birth (next[8] = previous[0])
         //  and reset the birth countdown (next[6] = previous[7] + previous[0])
         age_groups = tick_age_group(age_groups);
         //print_age_group(&age_groups);
+        day += 1;
+    }
+
+    age_groups
+}
+
+const fn count_lanternfishs(age_groups: [usize; 9]) -> usize {
+    // iterators are not available in const fn
+    let mut sum = 0;
+    let mut age = 0;
+    while age < age_groups.len() {
+        sum += age_groups[age];
+        age += 1;
     }
 
-    // then, just add up all currently existing lanternfish
-    age_groups.iter().sum()
+    sum
 }
 
 fn print_age_group(numbers: &[usize]) {
@@ -48,7 +131,7 @@ fn print_age_group(numbers: &[usize]) {
     );
 }
 
-fn tick_age_group(yesterday: [usize; 9]) -> [usize; 9] {
+const fn tick_age_group(yesterday: [usize; 9]) -> [usize; 9] {
     [
         yesterday[1],
         yesterday[2],
@@ -63,8 +146,38 @@ fn tick_age_group(yesterday: [usize; 9]) -> [usize; 9] {
 }
 
 fn main() {
-    println!("part 1: {}", simulate_lanternfishs(&parse_input(INPUT), 80));
-    println!("part 2: {}", simulate_lanternfishs(&parse_input(INPUT), 256));
+    let args = Args::from_env(&[Command::Solve, Command::Check]);
+    let input = args.input(INPUT);
+    let params = Params::from_args("day06", PARAMS, &args);
+    if args.command == Command::Check {
+        std::process::exit(check::report(&check_input(&input)));
+    }
+
+    let cache = ResultCache::new("day06", args.cache)
+        .or_exit()
+        .with_variant(&params);
+
+    // the compiler already solved the bundled input for the default number of days
+    let bundled = args.input.is_none();
+    let res: usize = match (bundled, params.get("part1_days")) {
+        (true, 80) => PART1,
+        (_, days) => cache
+            .solve("part1", "age_groups", input.as_bytes(), || {
+                simulate_lanternfishs(&parse_input(&input), days)
+            })
+            .or_exit(),
+    };
+    println!("part 1: {}", res);
+
+    let res: usize = match (bundled, params.get("part2_days")) {
+        (true, 256) => PART2,
+        (_, days) => cache
+            .solve("part2", "age_groups", input.as_bytes(), || {
+                simulate_lanternfishs(&parse_input(&input), days)
+            })
+            .or_exit(),
+    };
+    println!("part 2: {}", res);
 }
 
 #[cfg(test)]
@@ -78,7 +191,32 @@ mod test {
 
     #[test]
     fn test_day06_part2() {
-        assert_eq!(1590327954513, simulate_lanternfishs(&parse_input(INPUT), 256));
+        assert_eq!(
+            1590327954513,
+            simulate_lanternfishs(&parse_input(INPUT), 256)
+        );
+    }
+
+    #[test]
+    fn test_const_answers_match_runtime() {
+        assert_eq!(PART1, simulate_lanternfishs(&parse_input(INPUT), 80));
+        assert_eq!(PART2, simulate_lanternfishs(&parse_input(INPUT), 256));
+    }
+
+    #[test]
+    fn test_sample_const() {
+        const SAMPLE: [usize; 9] = parse_age_groups(b"3,4,3,1,2\n");
+        const SAMPLE_18: usize = count_lanternfishs(simulate_age_groups(SAMPLE, 18));
+        assert_eq!(26, SAMPLE_18);
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert!(check_input(INPUT).is_empty());
+
+        let problems = check_input("3,4,9,1,x\n2\n");
+        assert_eq!(3, problems.len());
+        assert_eq!(2, problems[2].line);
     }
 
     #[test]
@@ -0,0 +1,268 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const DEFAULT_CACHE_DIR: &str = ".aoc-cache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// return cached results if there are any, store new ones
+    #[default]
+    Use,
+    /// always compute, never read or write the cache
+    Bypass,
+    /// drop all cached results of the day, then behave like `Use`
+    Clear,
+    /// always compute and compare against the cached result (if any)
+    Verify,
+}
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    Mismatch {
+        key: String,
+        cached: String,
+        computed: String,
+    },
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheError::Io(err) => write!(f, "cache io error: {}", err),
+            CacheError::Mismatch {
+                key,
+                cached,
+                computed,
+            } => write!(
+                f,
+                "cached result for {} is {}, but computed {}",
+                key, cached, computed
+            ),
+        }
+    }
+}
+
+impl CacheError {
+    /// The process exit code for this error, distinct from the ones of solver and usage errors
+    /// (1 and 2) and of panics (101).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CacheError::Io(_) => 3,
+            CacheError::Mismatch { .. } => 4,
+        }
+    }
+}
+
+/// Ends the process with a readable message instead of a panic when the cache fails.
+pub trait OrExit<T> {
+    /// Unwraps the result, or prints the error to stderr and exits with its `exit_code`.
+    fn or_exit(self) -> T;
+}
+
+impl<T> OrExit<T> for Result<T, CacheError> {
+    fn or_exit(self) -> T {
+        self.unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(err.exit_code())
+        })
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        CacheError::Io(err)
+    }
+}
+
+/// On-disk store for puzzle answers.
+///
+/// Every answer lives in its own file, named after the part, the implementation used to compute
+/// it, a hash of the input bytes and the crate version. Changing any of those results in a cache
+/// miss, so stale answers are never returned after the input or the code changed.
+#[derive(Debug)]
+pub struct ResultCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    variant: String,
+}
+
+impl ResultCache {
+    /// Opens the cache for `day` below `$AOC_CACHE_DIR` (or `.aoc-cache`).
+    pub fn new(day: &str, mode: CacheMode) -> Result<ResultCache, CacheError> {
+        let root = std::env::var_os("AOC_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR));
+        ResultCache::with_dir(root.join(day), mode)
+    }
+
+    pub fn with_dir(dir: impl AsRef<Path>, mode: CacheMode) -> Result<ResultCache, CacheError> {
+        let dir = dir.as_ref().to_path_buf();
+        if mode == CacheMode::Clear && dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+
+        Ok(ResultCache {
+            dir,
+            mode,
+            variant: String::new(),
+        })
+    }
+
+    /// Separates results computed with different settings (e.g. tuned parameters) for the same
+    /// input.
+    pub fn with_variant(mut self, variant: impl Display) -> ResultCache {
+        self.variant = variant.to_string();
+        self
+    }
+
+    /// Returns the cached answer for `part`, or computes (and stores) it using `solve`.
+    pub fn solve<T, F>(
+        &self,
+        part: &str,
+        implementation: &str,
+        input: &[u8],
+        solve: F,
+    ) -> Result<T, CacheError>
+    where
+        T: Display + FromStr,
+        F: FnOnce() -> T,
+    {
+        let key = cache_key(part, implementation, input, &self.variant);
+        let path = self.dir.join(&key);
+
+        match self.mode {
+            CacheMode::Bypass => Ok(solve()),
+            CacheMode::Use | CacheMode::Clear => {
+                if let Some(cached) = read_entry(&path)?.and_then(|s| s.parse().ok()) {
+                    return Ok(cached);
+                }
+
+                let result = solve();
+                self.write_entry(&path, &result)?;
+                Ok(result)
+            }
+            CacheMode::Verify => {
+                let result = solve();
+                match read_entry(&path)? {
+                    Some(cached) if cached != result.to_string() => Err(CacheError::Mismatch {
+                        key,
+                        cached,
+                        computed: result.to_string(),
+                    }),
+                    Some(_) => Ok(result),
+                    None => {
+                        self.write_entry(&path, &result)?;
+                        Ok(result)
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_entry(&self, path: &Path, value: &impl Display) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, value.to_string())
+    }
+}
+
+fn read_entry(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        // entries edited by hand usually end with a newline
+        Ok(content) => Ok(Some(content.trim_end().to_string())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn cache_key(part: &str, implementation: &str, input: &[u8], variant: &str) -> String {
+    format!(
+        "{}-{}-{:016x}-v{}",
+        part,
+        implementation,
+        fnv1a(input) ^ fnv1a(variant.as_bytes()).rotate_left(1),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// 64 bit FNV-1a, stable across builds and platforms (unlike `DefaultHasher`).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_reuses_result() {
+        let dir = test_dir("reuse");
+        let calls = Cell::new(0);
+        let solve = || {
+            calls.set(calls.get() + 1);
+            42usize
+        };
+
+        let cache = ResultCache::with_dir(&dir, CacheMode::Use).unwrap();
+        assert_eq!(42, cache.solve("part1", "naive", b"1\n2\n", solve).unwrap());
+        assert_eq!(42, cache.solve("part1", "naive", b"1\n2\n", solve).unwrap());
+        assert_eq!(1, calls.get());
+
+        // different input, different key
+        assert_eq!(42, cache.solve("part1", "naive", b"1\n3\n", solve).unwrap());
+        assert_eq!(2, calls.get());
+    }
+
+    #[test]
+    fn test_cache_bypass_and_clear() {
+        let dir = test_dir("bypass");
+        let bypass = ResultCache::with_dir(&dir, CacheMode::Bypass).unwrap();
+        assert_eq!(1, bypass.solve("part1", "naive", b"", || 1).unwrap());
+        assert!(!dir.exists());
+
+        let cache = ResultCache::with_dir(&dir, CacheMode::Use).unwrap();
+        assert_eq!(1, cache.solve("part1", "naive", b"", || 1).unwrap());
+        assert_eq!(1, cache.solve("part1", "naive", b"", || 2).unwrap());
+
+        let cleared = ResultCache::with_dir(&dir, CacheMode::Clear).unwrap();
+        assert_eq!(2, cleared.solve("part1", "naive", b"", || 2).unwrap());
+    }
+
+    #[test]
+    fn test_cache_verify_detects_mismatch() {
+        let dir = test_dir("verify");
+        let cache = ResultCache::with_dir(&dir, CacheMode::Use).unwrap();
+        cache.solve("part2", "simd", b"x", || 7).unwrap();
+
+        let verify = ResultCache::with_dir(&dir, CacheMode::Verify).unwrap();
+        assert_eq!(7, verify.solve("part2", "simd", b"x", || 7).unwrap());
+        let err = verify.solve("part2", "simd", b"x", || 8).unwrap_err();
+        assert!(matches!(err, CacheError::Mismatch { .. }));
+        assert_eq!(4, err.exit_code());
+        assert!(err.to_string().ends_with(" is 7, but computed 8"));
+    }
+
+    #[test]
+    fn test_cache_separates_variants() {
+        let dir = test_dir("variant");
+        let cache = ResultCache::with_dir(&dir, CacheMode::Use).unwrap();
+        assert_eq!(1, cache.solve("part1", "naive", b"", || 1).unwrap());
+
+        let tuned = ResultCache::with_dir(&dir, CacheMode::Use)
+            .unwrap()
+            .with_variant("days=18");
+        assert_eq!(2, tuned.solve("part1", "naive", b"", || 2).unwrap());
+    }
+}
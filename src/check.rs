@@ -0,0 +1,38 @@
+use std::fmt::{Display, Formatter};
+
+/// A single problem found while validating an input file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(line: usize, message: impl Into<String>) -> Problem {
+        Problem {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Prints every problem and returns the exit code for the `check` command.
+pub fn report(problems: &[Problem]) -> i32 {
+    if problems.is_empty() {
+        println!("input ok");
+        return 0;
+    }
+
+    for problem in problems {
+        println!("{}", problem);
+    }
+    println!("{} problem(s) found", problems.len());
+    1
+}
@@ -0,0 +1,237 @@
+use crate::cache::CacheMode;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: dayNN [check | stream | stats | export | plan] [--input FILE] \
+    [--config FILE] [--param NAME=VALUE]... [--json] [--output DIR] \
+    [--no-cache | --clear-cache | --verify]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Command {
+    /// compute the answers
+    #[default]
+    Solve,
+    /// validate the input and report every problem found
+    Check,
+    /// process readings from stdin as they arrive (day01 only)
+    Stream,
+    /// print statistics about the input (day01 only)
+    Stats,
+    /// write the submarine trajectory as CSV and SVG (day02 only)
+    Export,
+    /// print commands that reach a target position (day02 only)
+    Plan,
+}
+
+impl Command {
+    /// the subcommand that selects this command
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Solve => "solve",
+            Command::Check => "check",
+            Command::Stream => "stream",
+            Command::Stats => "stats",
+            Command::Export => "export",
+            Command::Plan => "plan",
+        }
+    }
+}
+
+/// Command line options shared by all days.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    /// read the puzzle input from this file instead of the embedded one
+    pub input: Option<PathBuf>,
+    /// read parameters from this file instead of `aoc.toml`
+    pub config: Option<PathBuf>,
+    /// raw `--param name=value` overrides, in order
+    pub params: Vec<(String, String)>,
+    /// print reports as JSON instead of a text table
+    pub json: bool,
+    /// directory exported files are written to
+    pub output: Option<PathBuf>,
+    pub cache: CacheMode,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArgsError {
+    UnknownArgument(String),
+    MissingValue(String),
+    InvalidParam(String),
+    /// the subcommand exists, but the day does not implement it
+    UnsupportedCommand(Command),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::UnknownArgument(arg) => write!(f, "unknown argument {}", arg),
+            ArgsError::MissingValue(arg) => write!(f, "missing value for {}", arg),
+            ArgsError::InvalidParam(arg) => write!(f, "expected NAME=VALUE, got {}", arg),
+            ArgsError::UnsupportedCommand(command) => {
+                write!(
+                    f,
+                    "this day does not support the {} command",
+                    command.name()
+                )
+            }
+        }
+    }
+}
+
+impl Args {
+    /// Parses the process arguments, exits with the usage text if they are invalid or select a
+    /// command that is not in `supported`.
+    pub fn from_env(supported: &[Command]) -> Args {
+        Args::parse(std::env::args().skip(1), supported).unwrap_or_else(|err| {
+            eprintln!("{}\n{}", err, USAGE);
+            std::process::exit(2);
+        })
+    }
+
+    pub fn parse(
+        args: impl IntoIterator<Item = String>,
+        supported: &[Command],
+    ) -> Result<Args, ArgsError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().enumerate();
+        while let Some((idx, arg)) = args.next() {
+            match arg.as_str() {
+                "check" if idx == 0 => parsed.command = Command::Check,
+                "stream" if idx == 0 => parsed.command = Command::Stream,
+                "stats" if idx == 0 => parsed.command = Command::Stats,
+                "export" if idx == 0 => parsed.command = Command::Export,
+                "plan" if idx == 0 => parsed.command = Command::Plan,
+                "--json" => parsed.json = true,
+                "--input" => match args.next() {
+                    Some((_, path)) => parsed.input = Some(PathBuf::from(path)),
+                    None => return Err(ArgsError::MissingValue(arg)),
+                },
+                "--output" => match args.next() {
+                    Some((_, path)) => parsed.output = Some(PathBuf::from(path)),
+                    None => return Err(ArgsError::MissingValue(arg)),
+                },
+                "--config" => match args.next() {
+                    Some((_, path)) => parsed.config = Some(PathBuf::from(path)),
+                    None => return Err(ArgsError::MissingValue(arg)),
+                },
+                "--param" => match args.next() {
+                    Some((_, param)) => match param.split_once('=') {
+                        Some((name, value)) => parsed
+                            .params
+                            .push((name.trim().to_string(), value.trim().to_string())),
+                        None => return Err(ArgsError::InvalidParam(param)),
+                    },
+                    None => return Err(ArgsError::MissingValue(arg)),
+                },
+                "--no-cache" => parsed.cache = CacheMode::Bypass,
+                "--clear-cache" => parsed.cache = CacheMode::Clear,
+                "--verify" => parsed.cache = CacheMode::Verify,
+                _ => return Err(ArgsError::UnknownArgument(arg)),
+            }
+        }
+
+        if !supported.contains(&parsed.command) {
+            return Err(ArgsError::UnsupportedCommand(parsed.command));
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the content of `--input`, or the embedded puzzle input if none was given.
+    pub fn input(&self, embedded: &'static str) -> Cow<'static, str> {
+        match &self.input {
+            None => Cow::Borrowed(embedded),
+            Some(path) => Cow::Owned(std::fs::read_to_string(path).unwrap_or_else(|err| {
+                eprintln!("could not read {}: {}", path.display(), err);
+                std::process::exit(2);
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALL: &[Command] = &[
+        Command::Solve,
+        Command::Check,
+        Command::Stream,
+        Command::Stats,
+        Command::Export,
+        Command::Plan,
+    ];
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(|s| s.to_string()), ALL)
+    }
+
+    #[test]
+    fn test_parse_cache_flags() {
+        assert_eq!(CacheMode::Use, parse(&[]).unwrap().cache);
+        assert_eq!(CacheMode::Bypass, parse(&["--no-cache"]).unwrap().cache);
+        assert_eq!(CacheMode::Clear, parse(&["--clear-cache"]).unwrap().cache);
+        assert_eq!(CacheMode::Verify, parse(&["--verify"]).unwrap().cache);
+        assert_eq!(
+            Err(ArgsError::UnknownArgument("--nope".to_string())),
+            parse(&["--nope"])
+        );
+    }
+
+    #[test]
+    fn test_parse_check_command() {
+        let args = parse(&["check", "--input", "day03.txt"]).unwrap();
+        assert_eq!(Command::Check, args.command);
+        assert_eq!(Some(PathBuf::from("day03.txt")), args.input);
+
+        assert_eq!(Command::Solve, parse(&[]).unwrap().command);
+        assert_eq!(Command::Stream, parse(&["stream"]).unwrap().command);
+        assert!(parse(&["stats", "--json"]).unwrap().json);
+        let args = parse(&["export", "--output", "out"]).unwrap();
+        assert_eq!(Command::Export, args.command);
+        assert_eq!(Some(PathBuf::from("out")), args.output);
+        assert_eq!(Command::Plan, parse(&["plan"]).unwrap().command);
+        assert!(parse(&["--input", "x", "check"]).is_err());
+        assert_eq!(
+            Err(ArgsError::MissingValue("--input".to_string())),
+            parse(&["--input"])
+        );
+    }
+
+    #[test]
+    fn test_parse_unsupported_command() {
+        let supported = &[Command::Solve, Command::Check];
+        let parse = |args: &[&str]| Args::parse(args.iter().map(|s| s.to_string()), supported);
+        assert_eq!(Command::Check, parse(&["check"]).unwrap().command);
+        assert_eq!(
+            Err(ArgsError::UnsupportedCommand(Command::Stream)),
+            parse(&["stream"])
+        );
+        assert_eq!(
+            "this day does not support the plan command",
+            parse(&["plan", "--no-cache"]).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let args = parse(&[
+            "--param", "days=18", "--config", "x.toml", "--param", "w = 2",
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                ("days".to_string(), "18".to_string()),
+                ("w".to_string(), "2".to_string())
+            ],
+            args.params
+        );
+        assert_eq!(Some(PathBuf::from("x.toml")), args.config);
+        assert_eq!(
+            Err(ArgsError::InvalidParam("days".to_string())),
+            parse(&["--param", "days"])
+        );
+    }
+}
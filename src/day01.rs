@@ -0,0 +1,1243 @@
+use aoc_2021::cache::{OrExit, ResultCache};
+use aoc_2021::check::{self, Problem};
+use aoc_2021::cli::{Args, Command};
+use aoc_2021::params::{Param, Params};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::{Display, Formatter, Write};
+use std::io::BufRead;
+use std::simd::Simd;
+
+const INPUT: &str = include_str!("input/day01.txt");
+const SIMD_LANES: usize = 8;
+// readings per rayon task: 256 KiB of u32s, a task only spends one simd compare per
+// `SIMD_LANES` of them
+const PAR_CHUNK_SIZE: usize = 1 << 16;
+const PARAMS: &[Param] = &[
+    Param {
+        name: "window",
+        default: 3,
+        help: "width of the sliding window compared in part 2",
+        choices: &[],
+    },
+    Param {
+        name: "stats_window",
+        default: 3,
+        help: "width of the sliding window in the stats report",
+        choices: &[],
+    },
+    Param {
+        name: "bucket_width",
+        default: 1000,
+        help: "depth range covered by each histogram bucket in the stats report",
+        choices: &[],
+    },
+    Param {
+        name: "change_threshold",
+        default: 10,
+        help: "smallest change between windows that counts as significant in the stats report",
+        choices: &[],
+    },
+];
+
+pub fn parse_input(input: &str) -> Vec<u32> {
+    parse_depths(input.as_bytes()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// The original `str::parse` based parser, kept as the baseline for the benches.
+pub fn parse_input_naive(input: &str) -> Vec<u32> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse().unwrap())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+    InvalidByte(u8),
+    Overflow,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    /// 1-based
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::InvalidByte(b) => write!(f, "unexpected {:?}", *b as char),
+            ParseErrorKind::Overflow => write!(f, "number does not fit into a u32"),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+/// Parses newline separated unsigned integers, skipping blank lines.
+///
+/// Works on bytes instead of `&str` lines, and converts up to eight digits at once (SWAR: the
+/// digits are loaded into a single u64 and combined with three multiplications).
+pub fn parse_depths(input: &[u8]) -> Result<Vec<u32>, ParseError> {
+    // a depth has 4 digits and a newline in the puzzle input
+    let mut numbers = Vec::with_capacity(input.len() / 5);
+    let mut pos = 0;
+    let mut line = 1;
+    let mut line_start = 0;
+    let error = |pos: usize, line: usize, line_start: usize, kind| ParseError {
+        line,
+        column: pos - line_start + 1,
+        kind,
+    };
+
+    while pos < input.len() {
+        let digits = count_digits(&input[pos..]);
+        if digits == 0 {
+            // nothing but whitespace until the end of the line is a blank line
+            let rest = &input[pos..];
+            let line_len = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            match rest[..line_len]
+                .iter()
+                .position(|b| !b.is_ascii_whitespace())
+            {
+                Some(offset) => {
+                    let pos = pos + offset;
+                    return Err(error(
+                        pos,
+                        line,
+                        line_start,
+                        ParseErrorKind::InvalidByte(input[pos]),
+                    ));
+                }
+                None => {
+                    pos += line_len + 1;
+                    line += 1;
+                    line_start = pos;
+                    continue;
+                }
+            }
+        }
+
+        let value = parse_digits(&input[pos..pos + digits])
+            .ok_or_else(|| error(pos, line, line_start, ParseErrorKind::Overflow))?;
+        numbers.push(value);
+        pos += digits;
+
+        // the number has to be followed by the end of the line
+        if pos < input.len() && input[pos] == b'\r' {
+            pos += 1;
+        }
+        match input.get(pos) {
+            None => {}
+            Some(b'\n') => {
+                pos += 1;
+                line += 1;
+                line_start = pos;
+            }
+            Some(&b) => return Err(error(pos, line, line_start, ParseErrorKind::InvalidByte(b))),
+        }
+    }
+
+    Ok(numbers)
+}
+
+const ASCII_ZEROS: u64 = 0x3030_3030_3030_3030;
+
+/// number of ascii digits `bytes` starts with
+#[inline(always)]
+fn count_digits(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    while bytes.len() - count >= 8 {
+        let chunk = u64::from_le_bytes(bytes[count..count + 8].try_into().unwrap());
+        let non_digits = non_digit_mask(chunk);
+        if non_digits != 0 {
+            // little endian: the first byte is the lowest one
+            return count + non_digits.trailing_zeros() as usize / 8;
+        }
+        count += 8;
+    }
+
+    count
+        + bytes[count..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+}
+
+/// sets the high bit of every byte of `chunk` that is not an ascii digit
+#[inline(always)]
+fn non_digit_mask(chunk: u64) -> u64 {
+    // a byte is a digit if its high nibble is 3, and adding 6 keeps it at 3 (b'0'..=b'9')
+    let high_nibble_ok = (chunk & 0xF0F0_F0F0_F0F0_F0F0) ^ ASCII_ZEROS;
+    let below_ten =
+        ((chunk & 0x0F0F_0F0F_0F0F_0F0F) + 0x0606_0606_0606_0606) & 0xF0F0_F0F0_F0F0_F0F0;
+    // any bit set in a byte marks it as a non digit, fold them into the high bit
+    let bad = high_nibble_ok | below_ten;
+    (bad | (bad << 1) | (bad << 2) | (bad << 3)) & 0x8080_8080_8080_8080
+}
+
+/// converts a run of ascii digits, `None` if it does not fit into a u32
+#[inline(always)]
+fn parse_digits(digits: &[u8]) -> Option<u32> {
+    let (head, tail) = digits.split_at(digits.len().min(8));
+    let mut value = swar_parse_8(head);
+    for &digit in tail {
+        value = value.checked_mul(10)?.checked_add((digit - b'0') as u32)?;
+    }
+
+    Some(value)
+}
+
+/// converts up to eight ascii digits at once
+#[inline(always)]
+fn swar_parse_8(digits: &[u8]) -> u32 {
+    debug_assert!(!digits.is_empty() && digits.len() <= 8);
+    let mut bytes = [b'0'; 8];
+    // right align the digits, the padding acts as leading zeros
+    bytes[8 - digits.len()..].copy_from_slice(digits);
+    let mut chunk = u64::from_le_bytes(bytes) - ASCII_ZEROS;
+
+    // combine neighbouring digits into 2, then 4, then 8 digit numbers
+    chunk = (chunk * 10) + (chunk >> 8);
+    chunk = ((chunk & 0x0000_00FF_0000_00FF).wrapping_mul(100 + (1_000_000 << 32))
+        + ((chunk >> 16) & 0x0000_00FF_0000_00FF).wrapping_mul(1 + (10_000 << 32)))
+        >> 32;
+    chunk as u32
+}
+
+/// Every line `parse_depths` rejects, instead of only the first one.
+fn check_input(input: &str) -> Vec<Problem> {
+    // `parse_depths` handles each line on its own, so it can be run line by line
+    input
+        .split('\n')
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let err = parse_depths(line.as_bytes()).err()?;
+            Some(Problem::new(
+                idx + 1,
+                format!("column {}: {}", err.column, err.kind),
+            ))
+        })
+        .collect()
+}
+
+/// How two neighbouring (aggregated) windows are compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// the later window is larger
+    Increase,
+    /// the later window is larger or equal
+    NonDecrease,
+    /// the later window is smaller
+    Decrease,
+    /// the windows differ by more than the given delta, in either direction
+    ChangeAbove(u32),
+}
+
+impl Comparison {
+    /// `scale` is the factor both values were multiplied with (the window width for means that
+    /// are compared as sums), so thresholds can be scaled instead of dividing the values.
+    fn matches(self, a: u64, b: u64, scale: u64) -> bool {
+        match self {
+            Comparison::Increase => b > a,
+            Comparison::NonDecrease => b >= a,
+            Comparison::Decrease => b < a,
+            Comparison::ChangeAbove(delta) => a.max(b) - a.min(b) > delta as u64 * scale,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Increase => write!(f, "increase"),
+            Comparison::NonDecrease => write!(f, "non-decrease"),
+            Comparison::Decrease => write!(f, "decrease"),
+            Comparison::ChangeAbove(delta) => write!(f, "change > {}", delta),
+        }
+    }
+}
+
+/// How the readings of a window are combined into one value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Sum,
+    Mean,
+    Max,
+}
+
+impl Display for Aggregation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Aggregation::Sum => write!(f, "sum"),
+            Aggregation::Mean => write!(f, "mean"),
+            Aggregation::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// Counts how often two neighbouring `window` wide windows match `comparison`.
+///
+/// Two neighbouring windows share all but their first and last element, so comparing the sums
+/// is the same as comparing `numbers[i]` with `numbers[i + window]`. No sums are needed at all.
+/// The same holds for means, which are sums divided by the same width.
+pub fn count_window_changes(
+    numbers: &[u32],
+    window: usize,
+    aggregation: Aggregation,
+    comparison: Comparison,
+) -> usize {
+    let scale = match aggregation {
+        Aggregation::Sum => 1,
+        Aggregation::Mean => window as u64,
+        Aggregation::Max => {
+            let maxs: Vec<u32> = sliding_min_max(numbers, window)
+                .into_iter()
+                .map(|(_, max)| max)
+                .collect();
+            return maxs
+                .windows(2)
+                .filter(|pair| comparison.matches(pair[0] as u64, pair[1] as u64, 1))
+                .count();
+        }
+    };
+
+    numbers
+        .iter()
+        .zip(numbers.iter().skip(window))
+        //.fold(0, |acc, (a, b)| acc + (if b > a { 1 } else { 0 }));
+        .filter(|(&a, &b)| comparison.matches(a as u64, b as u64, scale))
+        .count()
+}
+
+/// Counts how often the sum of a `window` wide sliding window increases (the puzzle's question).
+pub fn count_window_increases(numbers: &[u32], window: usize) -> usize {
+    count_window_changes(numbers, window, Aggregation::Sum, Comparison::Increase)
+}
+
+/// Same as `count_window_increases`, comparing `SIMD_LANES` pairs at once.
+pub fn count_window_increases_simd(numbers: &[u32], window: usize) -> usize {
+    if window >= numbers.len() {
+        return 0;
+    }
+
+    count_increases_simd(&numbers[..numbers.len() - window], &numbers[window..])
+}
+
+/// Same as `count_window_increases_simd`, spread over all cores.
+///
+/// Instead of chunking `numbers` (and having to look at the pairs that cross a chunk boundary
+/// separately), both sides of the comparison are chunked: chunk `n` of `before` lines up with
+/// chunk `n` of `after`, which already contains the readings `window` positions later.
+pub fn count_window_increases_par(numbers: &[u32], window: usize) -> usize {
+    if window >= numbers.len() {
+        return 0;
+    }
+
+    let before = &numbers[..numbers.len() - window];
+    let after = &numbers[window..];
+    before
+        .par_chunks(PAR_CHUNK_SIZE)
+        .zip(after.par_chunks(PAR_CHUNK_SIZE))
+        .map(|(before, after)| count_increases_simd(before, after))
+        .sum()
+}
+
+/// Counts the positions where `after` is larger than `before`, both need the same length.
+fn count_increases_simd(before: &[u32], after: &[u32]) -> usize {
+    debug_assert_eq!(before.len(), after.len());
+    let before_chunks = before.chunks_exact(SIMD_LANES);
+    let after_chunks = after.chunks_exact(SIMD_LANES);
+    let remainder = before_chunks
+        .remainder()
+        .iter()
+        .zip(after_chunks.remainder())
+        .filter(|(a, b)| b > a)
+        .count();
+
+    // a true lane is -1 as an int, so subtracting the mask counts the increases per lane
+    let mut lane_counts = Simd::<i32, SIMD_LANES>::splat(0);
+    for (a, b) in before_chunks.zip(after_chunks) {
+        let a = Simd::<u32, SIMD_LANES>::from_slice(a);
+        let b = Simd::<u32, SIMD_LANES>::from_slice(b);
+        lane_counts -= b.lanes_gt(a).to_int();
+    }
+
+    lane_counts.horizontal_sum() as usize + remainder
+}
+
+fn part1(numbers: &[u32]) -> usize {
+    count_window_increases(numbers, 1)
+}
+
+fn part2(numbers: &[u32], window: usize) -> usize {
+    count_window_increases(numbers, window)
+}
+
+/// Counts depth increases for several window sizes at once, one reading at a time.
+///
+/// Only the last `max(windows)` readings are kept (in a ring buffer), so this works on endless
+/// sonar feeds just like `count_window_increases` does on a finished list.
+pub struct DepthDetector {
+    windows: Vec<usize>,
+    counts: Vec<usize>,
+    history: Vec<u32>,
+    // position in `history` the next reading is written to
+    next: usize,
+    seen: usize,
+}
+
+impl DepthDetector {
+    /// Counts for each of `windows`, a window of 0 never counts an increase.
+    pub fn new(windows: &[usize]) -> DepthDetector {
+        let capacity = windows.iter().copied().max().unwrap_or(0).max(1);
+        DepthDetector {
+            windows: windows.to_vec(),
+            counts: vec![0; windows.len()],
+            history: vec![0; capacity],
+            next: 0,
+            seen: 0,
+        }
+    }
+
+    /// Adds a reading, returns the increase counts so far (in the order of the windows).
+    pub fn push(&mut self, depth: u32) -> &[usize] {
+        let capacity = self.history.len();
+        for (&window, count) in self.windows.iter().zip(self.counts.iter_mut()) {
+            // same trick as in count_window_increases: compare with the reading `window` ago
+            if window > 0 && self.seen >= window {
+                let previous = self.history[(self.next + capacity - window) % capacity];
+                if depth > previous {
+                    *count += 1;
+                }
+            }
+        }
+
+        self.history[self.next] = depth;
+        self.next = (self.next + 1) % capacity;
+        self.seen += 1;
+        &self.counts
+    }
+
+    /// The increase counts so far, in the order of the windows.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+}
+
+/// Reads depths from stdin as they arrive and prints the running counts after each of them.
+fn stream(windows: &[usize]) {
+    let mut detector = DepthDetector::new(windows);
+    for (idx, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.expect("could not read from stdin");
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.trim().parse() {
+            Ok(depth) => {
+                let counts = detector.push(depth);
+                let columns: Vec<String> = windows
+                    .iter()
+                    .zip(counts)
+                    .map(|(window, count)| format!("window {}: {}", window, count))
+                    .collect();
+                println!("{} {}", depth, columns.join(", "));
+            }
+            Err(_) => eprintln!("line {}: not a depth reading: {:?}", idx + 1, line),
+        }
+    }
+
+    let totals: Vec<String> = windows
+        .iter()
+        .zip(detector.counts())
+        .map(|(window, count)| format!("window {}: {}", window, count))
+        .collect();
+    println!("total {}", totals.join(", "));
+}
+
+#[derive(Debug, PartialEq)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct Drop {
+    /// index of the reading after the drop
+    index: usize,
+    from: u32,
+    to: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct WindowStats {
+    start: usize,
+    min: u32,
+    max: u32,
+    /// for windows of even width, the lower of the two middle values
+    median: u32,
+}
+
+#[derive(Debug)]
+struct DepthStats {
+    readings: usize,
+    longest_increasing_run: Run,
+    largest_drop: Option<Drop>,
+    bucket_width: u32,
+    /// (start of the bucket, number of readings in it), empty buckets are left out
+    histogram: Vec<(u32, usize)>,
+    window: usize,
+    windows: Vec<WindowStats>,
+    /// how often neighbouring windows match each comparison, per aggregation
+    changes: Vec<(Aggregation, Comparison, usize)>,
+}
+
+fn depth_stats(
+    numbers: &[u32],
+    bucket_width: u32,
+    window: usize,
+    change_threshold: u32,
+) -> DepthStats {
+    let min_max = sliding_min_max(numbers, window);
+    let medians = sliding_median(numbers, window);
+    let mut changes = vec![];
+    for aggregation in [Aggregation::Sum, Aggregation::Mean, Aggregation::Max] {
+        for comparison in [
+            Comparison::Increase,
+            Comparison::NonDecrease,
+            Comparison::Decrease,
+            Comparison::ChangeAbove(change_threshold),
+        ] {
+            let count = count_window_changes(numbers, window, aggregation, comparison);
+            changes.push((aggregation, comparison, count));
+        }
+    }
+
+    DepthStats {
+        readings: numbers.len(),
+        longest_increasing_run: longest_increasing_run(numbers),
+        largest_drop: largest_drop(numbers),
+        bucket_width,
+        histogram: histogram(numbers, bucket_width),
+        window,
+        windows: min_max
+            .into_iter()
+            .zip(medians)
+            .enumerate()
+            .map(|(start, ((min, max), median))| WindowStats {
+                start,
+                min,
+                max,
+                median,
+            })
+            .collect(),
+        changes,
+    }
+}
+
+/// first of the longest runs of strictly increasing readings
+fn longest_increasing_run(numbers: &[u32]) -> Run {
+    let mut longest = Run {
+        start: 0,
+        len: numbers.len().min(1),
+    };
+    let mut start = 0;
+    for idx in 1..numbers.len() {
+        if numbers[idx] <= numbers[idx - 1] {
+            start = idx;
+        } else if idx + 1 - start > longest.len {
+            longest = Run {
+                start,
+                len: idx + 1 - start,
+            };
+        }
+    }
+
+    longest
+}
+
+fn largest_drop(numbers: &[u32]) -> Option<Drop> {
+    numbers
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[1] < pair[0])
+        // max_by_key returns the last maximum, we want the first one
+        .rev()
+        .max_by_key(|(_, pair)| pair[0] - pair[1])
+        .map(|(idx, pair)| Drop {
+            index: idx + 1,
+            from: pair[0],
+            to: pair[1],
+        })
+}
+
+fn histogram(numbers: &[u32], bucket_width: u32) -> Vec<(u32, usize)> {
+    assert!(bucket_width > 0, "bucket width must be positive");
+    let mut buckets = std::collections::BTreeMap::new();
+    for &depth in numbers {
+        *buckets
+            .entry(depth / bucket_width * bucket_width)
+            .or_insert(0) += 1;
+    }
+
+    buckets.into_iter().collect()
+}
+
+/// Minimum and maximum of every `window` wide window.
+///
+/// Both use a monotonic deque of indices: values that can never become the min (max) again
+/// because a smaller (larger) one came after them are dropped, so the front is always the answer.
+fn sliding_min_max(numbers: &[u32], window: usize) -> Vec<(u32, u32)> {
+    if window == 0 || window > numbers.len() {
+        return vec![];
+    }
+
+    let mut mins: VecDeque<usize> = VecDeque::new();
+    let mut maxs: VecDeque<usize> = VecDeque::new();
+    let mut result = Vec::with_capacity(numbers.len() - window + 1);
+    for (idx, &depth) in numbers.iter().enumerate() {
+        while mins.back().map_or(false, |&i| numbers[i] >= depth) {
+            mins.pop_back();
+        }
+        while maxs.back().map_or(false, |&i| numbers[i] <= depth) {
+            maxs.pop_back();
+        }
+        mins.push_back(idx);
+        maxs.push_back(idx);
+
+        if idx + 1 < window {
+            continue;
+        }
+        // drop whatever slid out of the window
+        let window_start = idx + 1 - window;
+        while mins[0] < window_start {
+            mins.pop_front();
+        }
+        while maxs[0] < window_start {
+            maxs.pop_front();
+        }
+        result.push((numbers[mins[0]], numbers[maxs[0]]));
+    }
+
+    result
+}
+
+/// Median of every `window` wide window (the lower one for even windows).
+///
+/// The window is split into two heaps: a max-heap with the lower half, whose top is the median,
+/// and a min-heap with the upper half. Readings that slide out are only marked, and dropped once
+/// they reach the top of their heap, so every step is O(log window).
+fn sliding_median(numbers: &[u32], window: usize) -> Vec<u32> {
+    if window == 0 || window > numbers.len() {
+        return vec![];
+    }
+
+    let mut heaps = MedianHeaps::default();
+    let mut result = Vec::with_capacity(numbers.len() - window + 1);
+    for (idx, &depth) in numbers.iter().enumerate() {
+        heaps.insert(depth);
+        if idx + 1 < window {
+            continue;
+        }
+        if idx >= window {
+            heaps.remove(numbers[idx - window]);
+        }
+        result.push(heaps.median());
+    }
+
+    result
+}
+
+#[derive(Default)]
+struct MedianHeaps {
+    low: BinaryHeap<u32>,
+    high: BinaryHeap<Reverse<u32>>,
+    // readings removed from the window, but still somewhere in a heap
+    removed: HashMap<u32, usize>,
+    // readings in each heap that are still part of the window
+    low_len: usize,
+    high_len: usize,
+}
+
+impl MedianHeaps {
+    fn insert(&mut self, depth: u32) {
+        if self.low.peek().map_or(true, |&top| depth <= top) {
+            self.low.push(depth);
+            self.low_len += 1;
+        } else {
+            self.high.push(Reverse(depth));
+            self.high_len += 1;
+        }
+        self.rebalance();
+    }
+
+    fn remove(&mut self, depth: u32) {
+        *self.removed.entry(depth).or_insert(0) += 1;
+        // equal readings are interchangeable, so it does not matter which heap had this one
+        if depth <= *self.low.peek().unwrap() {
+            self.low_len -= 1;
+        } else {
+            self.high_len -= 1;
+        }
+        self.prune();
+        self.rebalance();
+    }
+
+    /// the top of the lower half, which has one more reading for odd windows
+    fn median(&self) -> u32 {
+        *self.low.peek().unwrap()
+    }
+
+    fn rebalance(&mut self) {
+        if self.low_len > self.high_len + 1 {
+            self.high.push(Reverse(self.low.pop().unwrap()));
+            self.low_len -= 1;
+            self.high_len += 1;
+        } else if self.low_len < self.high_len {
+            self.low.push(self.high.pop().unwrap().0);
+            self.high_len -= 1;
+            self.low_len += 1;
+        }
+        self.prune();
+    }
+
+    /// drops removed readings from the tops of both heaps
+    fn prune(&mut self) {
+        while let Some(&top) = self.low.peek() {
+            if !self.take_removed(top) {
+                break;
+            }
+            self.low.pop();
+        }
+        while let Some(&Reverse(top)) = self.high.peek() {
+            if !self.take_removed(top) {
+                break;
+            }
+            self.high.pop();
+        }
+    }
+
+    fn take_removed(&mut self, depth: u32) -> bool {
+        match self.removed.get_mut(&depth) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl DepthStats {
+    fn to_table(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "readings                 {:>8}", self.readings).unwrap();
+        writeln!(
+            out,
+            "longest increasing run   {:>8} readings, starting at #{}",
+            self.longest_increasing_run.len, self.longest_increasing_run.start
+        )
+        .unwrap();
+        match &self.largest_drop {
+            Some(drop) => writeln!(
+                out,
+                "largest drop             {:>8} ({} -> {} at #{})",
+                drop.from - drop.to,
+                drop.from,
+                drop.to,
+                drop.index
+            ),
+            None => writeln!(out, "largest drop                 none"),
+        }
+        .unwrap();
+
+        writeln!(out, "\n{:>8} {:>8} {:>8}", "from", "to", "count").unwrap();
+        for &(start, count) in self.histogram.iter() {
+            let end = start.saturating_add(self.bucket_width - 1);
+            writeln!(out, "{:>8} {:>8} {:>8}", start, end, count).unwrap();
+        }
+
+        writeln!(
+            out,
+            "\n{:>8} {:>14} {:>8}   (window of {})",
+            "window", "comparison", "count", self.window
+        )
+        .unwrap();
+        for (aggregation, comparison, count) in self.changes.iter() {
+            writeln!(
+                out,
+                "{:>8} {:>14} {:>8}",
+                aggregation.to_string(),
+                comparison.to_string(),
+                count
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "\n{:>8} {:>8} {:>8} {:>8}   (window of {})",
+            "start", "min", "max", "median", self.window
+        )
+        .unwrap();
+        for w in self.windows.iter() {
+            writeln!(
+                out,
+                "{:>8} {:>8} {:>8} {:>8}",
+                w.start, w.min, w.max, w.median
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    fn to_json(&self) -> String {
+        let run = &self.longest_increasing_run;
+        let drop = match &self.largest_drop {
+            Some(d) => format!(
+                "{{\"index\":{},\"from\":{},\"to\":{},\"amount\":{}}}",
+                d.index,
+                d.from,
+                d.to,
+                d.from - d.to
+            ),
+            None => "null".to_string(),
+        };
+        let histogram: Vec<String> = self
+            .histogram
+            .iter()
+            .map(|(start, count)| format!("{{\"from\":{},\"count\":{}}}", start, count))
+            .collect();
+        let windows: Vec<String> = self
+            .windows
+            .iter()
+            .map(|w| {
+                format!(
+                    "{{\"start\":{},\"min\":{},\"max\":{},\"median\":{}}}",
+                    w.start, w.min, w.max, w.median
+                )
+            })
+            .collect();
+        let changes: Vec<String> = self
+            .changes
+            .iter()
+            .map(|(aggregation, comparison, count)| {
+                format!(
+                    "{{\"aggregation\":\"{}\",\"comparison\":\"{}\",\"count\":{}}}",
+                    aggregation, comparison, count
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"readings\":{},\"longest_increasing_run\":{{\"start\":{},\"len\":{}}},\
+             \"largest_drop\":{},\"bucket_width\":{},\"histogram\":[{}],\
+             \"window\":{},\"windows\":[{}],\"changes\":[{}]}}",
+            self.readings,
+            run.start,
+            run.len,
+            drop,
+            self.bucket_width,
+            histogram.join(","),
+            self.window,
+            windows.join(","),
+            changes.join(",")
+        )
+    }
+}
+
+pub fn main() {
+    let args = Args::from_env(&[
+        Command::Solve,
+        Command::Check,
+        Command::Stream,
+        Command::Stats,
+    ]);
+    let input = args.input(INPUT);
+    let params = Params::from_args("day01", PARAMS, &args);
+    let bucket_width = params.get("bucket_width");
+    if bucket_width == 0 || bucket_width > u32::MAX as usize {
+        eprintln!("bucket_width must be between 1 and {}", u32::MAX);
+        std::process::exit(2);
+    }
+    match args.command {
+        Command::Check => std::process::exit(check::report(&check_input(&input))),
+        Command::Stream => return stream(&[1, params.get("window")]),
+        Command::Stats => {
+            let stats = depth_stats(
+                &read_depths(&input),
+                bucket_width as u32,
+                params.get("stats_window"),
+                params.get("change_threshold") as u32,
+            );
+            match args.json {
+                true => println!("{}", stats.to_json()),
+                false => print!("{}", stats.to_table()),
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let cache = ResultCache::new("day01", args.cache)
+        .or_exit()
+        .with_variant(&params);
+
+    let res: usize = cache
+        .solve("part1", "zip", input.as_bytes(), || {
+            part1(&read_depths(&input))
+        })
+        .or_exit();
+    println!("result 1: {}", res);
+
+    let res: usize = cache
+        .solve("part2", "zip", input.as_bytes(), || {
+            part2(&read_depths(&input), params.get("window"))
+        })
+        .or_exit();
+    println!("result 2: {}", res);
+}
+
+/// `parse_depths` for `main`, exits with the error instead of panicking.
+fn read_depths(input: &str) -> Vec<u32> {
+    parse_depths(input.as_bytes()).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::xorshift::XorShift;
+
+    #[test]
+    fn test_day01_part01_complete() {
+        let expected_result = 1553;
+        let result = part1(&parse_input(INPUT));
+
+        assert_eq!(expected_result, result);
+    }
+    #[test]
+    fn test_day01_part02_complete() {
+        let expected_result = 1597;
+        let result = part2(&parse_input(INPUT), 3);
+
+        assert_eq!(expected_result, result);
+    }
+
+    /// compares actual window sums, like part 2 used to
+    fn count_window_increases_by_sum(numbers: &[u32], window: usize) -> usize {
+        let sums: Vec<u64> = numbers
+            .windows(window)
+            .map(|w| w.iter().map(|&n| n as u64).sum())
+            .collect();
+        sums.windows(2).filter(|pair| pair[1] > pair[0]).count()
+    }
+
+    #[test]
+    fn test_count_window_increases_sample() {
+        let sample = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        assert_eq!(7, count_window_increases(&sample, 1));
+        assert_eq!(5, count_window_increases(&sample, 3));
+        assert_eq!(0, count_window_increases(&sample, 10));
+        assert_eq!(0, count_window_increases(&sample, 11));
+    }
+
+    /// aggregates every window and compares them, the slow way
+    fn count_window_changes_brute_force(
+        numbers: &[u32],
+        window: usize,
+        aggregation: Aggregation,
+        comparison: Comparison,
+    ) -> usize {
+        let values: Vec<f64> = numbers
+            .windows(window)
+            .map(|w| match aggregation {
+                Aggregation::Sum => w.iter().map(|&n| n as f64).sum(),
+                Aggregation::Mean => w.iter().map(|&n| n as f64).sum::<f64>() / window as f64,
+                Aggregation::Max => *w.iter().max().unwrap() as f64,
+            })
+            .collect();
+        values
+            .windows(2)
+            .filter(|pair| match comparison {
+                Comparison::Increase => pair[1] > pair[0],
+                Comparison::NonDecrease => pair[1] >= pair[0],
+                Comparison::Decrease => pair[1] < pair[0],
+                Comparison::ChangeAbove(delta) => (pair[1] - pair[0]).abs() > delta as f64,
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_count_window_changes_sample() {
+        use Aggregation::*;
+        use Comparison::*;
+        assert_eq!(7, count_window_changes(&SAMPLE, 1, Sum, Increase));
+        assert_eq!(2, count_window_changes(&SAMPLE, 1, Sum, Decrease));
+        // window sums: 607, 618, 618, 617, 647, 716, 769, 792
+        assert_eq!(6, count_window_changes(&SAMPLE, 3, Mean, NonDecrease));
+        // 208, 210, 210, 210, 240, 269, 269, 269
+        assert_eq!(3, count_window_changes(&SAMPLE, 3, Max, Increase));
+        assert_eq!(7, count_window_changes(&SAMPLE, 3, Max, NonDecrease));
+        // means change by more than 9 when sums change by more than 27
+        assert_eq!(3, count_window_changes(&SAMPLE, 3, Mean, ChangeAbove(9)));
+        assert_eq!(5, count_window_changes(&SAMPLE, 3, Sum, ChangeAbove(9)));
+    }
+
+    #[test]
+    fn test_count_window_changes_match_brute_force() {
+        let numbers = parse_input(INPUT);
+        for aggregation in [Aggregation::Sum, Aggregation::Mean, Aggregation::Max] {
+            for comparison in [
+                Comparison::Increase,
+                Comparison::NonDecrease,
+                Comparison::Decrease,
+                Comparison::ChangeAbove(0),
+                Comparison::ChangeAbove(5),
+                Comparison::ChangeAbove(40),
+            ] {
+                for window in [1, 2, 3, 10] {
+                    assert_eq!(
+                        count_window_changes_brute_force(&numbers, window, aggregation, comparison),
+                        count_window_changes(&numbers, window, aggregation, comparison),
+                        "{:?} {:?} {}",
+                        aggregation,
+                        comparison,
+                        window
+                    );
+                }
+            }
+        }
+    }
+
+    fn random_depths(len: usize, seed: u64) -> Vec<u32> {
+        XorShift::new(seed)
+            .take(len)
+            // small range, so equal neighbours (no increase) happen as well
+            .map(|n| (n % 64) as u32)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_depths() {
+        assert_eq!(Ok(vec![199, 200, 7]), parse_depths(b"199\n200\n\n7"));
+        assert_eq!(Ok(vec![1, 2]), parse_depths(b"1\r\n  \n2\r\n\n"));
+        assert_eq!(Ok(vec![]), parse_depths(b""));
+        assert_eq!(
+            Ok(vec![12345678, 123456789, 4294967295, 0, 42]),
+            parse_depths(b"12345678\n123456789\n4294967295\n000000000000\n0000000042\n")
+        );
+        assert_eq!(parse_input_naive(INPUT), parse_input(INPUT));
+    }
+
+    #[test]
+    fn test_parse_depths_errors() {
+        assert_eq!(
+            Err(ParseError {
+                line: 2,
+                column: 2,
+                kind: ParseErrorKind::InvalidByte(b'x')
+            }),
+            parse_depths(b"199\n2x0\n")
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 3,
+                column: 1,
+                kind: ParseErrorKind::Overflow
+            }),
+            parse_depths(b"1\n2\n4294967296\n")
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 1,
+                column: 2,
+                kind: ParseErrorKind::InvalidByte(b'-')
+            }),
+            parse_depths(b" -3\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_depths_matches_naive() {
+        for (len, seed) in [(1, 11), (100, 12), (10_000, 13)] {
+            // spread the values over all digit counts
+            let numbers: Vec<u32> = random_depths(len, seed)
+                .iter()
+                .enumerate()
+                .map(|(idx, &n)| n.wrapping_mul(0x9E37_79B9) >> (idx % 32))
+                .collect();
+            let text: String = numbers.iter().map(|n| format!("{}\n", n)).collect();
+
+            assert_eq!(Ok(numbers), parse_depths(text.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_simd_and_par_match_scalar() {
+        for (len, seed) in [
+            (0, 1),
+            (1, 2),
+            (7, 3),
+            (8, 4),
+            (9, 5),
+            (1000, 6),
+            (300_007, 7),
+        ] {
+            let numbers = random_depths(len, seed);
+            for window in [0, 1, 3, 8, 9, 1000, len] {
+                let expected = count_window_increases(&numbers, window);
+                assert_eq!(
+                    expected,
+                    count_window_increases_simd(&numbers, window),
+                    "simd: len {} window {}",
+                    len,
+                    window
+                );
+                assert_eq!(
+                    expected,
+                    count_window_increases_par(&numbers, window),
+                    "par: len {} window {}",
+                    len,
+                    window
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_and_par_on_input() {
+        let numbers = parse_input(INPUT);
+        assert_eq!(1553, count_window_increases_simd(&numbers, 1));
+        assert_eq!(1597, count_window_increases_par(&numbers, 3));
+    }
+
+    #[test]
+    fn test_count_window_increases_large_windows() {
+        let numbers = parse_input(INPUT);
+        for window in [1, 2, 3, 7, 64, 500, 1999, 2000] {
+            assert_eq!(
+                count_window_increases_by_sum(&numbers, window),
+                count_window_increases(&numbers, window),
+                "window {}",
+                window
+            );
+        }
+    }
+
+    #[test]
+    fn test_detector_matches_batch_counts() {
+        let numbers = parse_input(INPUT);
+        let windows = [1, 3, 10, 0];
+        let mut detector = DepthDetector::new(&windows);
+        for &depth in numbers.iter() {
+            detector.push(depth);
+        }
+
+        assert_eq!(
+            &[1553, 1597, count_window_increases(&numbers, 10), 0],
+            detector.counts()
+        );
+    }
+
+    #[test]
+    fn test_detector_running_counts() {
+        let mut detector = DepthDetector::new(&[3, 1]);
+        assert_eq!(&[0, 0], detector.push(199));
+        assert_eq!(&[0, 1], detector.push(200));
+        assert_eq!(&[0, 2], detector.push(208));
+        assert_eq!(&[1, 3], detector.push(210));
+        assert_eq!(&[1, 3], detector.push(200));
+        assert_eq!(&[1, 4], detector.push(207));
+    }
+
+    const SAMPLE: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_depth_stats_sample() {
+        let stats = depth_stats(&SAMPLE, 10, 3, 9);
+
+        assert_eq!(Run { start: 0, len: 4 }, stats.longest_increasing_run);
+        assert_eq!(
+            Some(Drop {
+                index: 4,
+                from: 210,
+                to: 200
+            }),
+            stats.largest_drop
+        );
+        assert_eq!(
+            vec![(190, 1), (200, 4), (210, 1), (240, 1), (260, 3)],
+            stats.histogram
+        );
+        assert_eq!(
+            WindowStats {
+                start: 5,
+                min: 207,
+                max: 269,
+                median: 240
+            },
+            stats.windows[5]
+        );
+        assert!(stats
+            .changes
+            .contains(&(Aggregation::Mean, Comparison::ChangeAbove(9), 3)));
+        assert!(stats
+            .to_json()
+            .starts_with("{\"readings\":10,\"longest_increasing_run\":{\"start\":0,\"len\":4},"));
+    }
+
+    #[test]
+    fn test_sliding_window_stats_match_brute_force() {
+        let input = parse_input(INPUT);
+        // lots of equal readings leave removed ones behind in both heaps
+        let repeated: Vec<u32> = input.iter().map(|depth| depth % 7).collect();
+        for numbers in [input, repeated] {
+            for window in [1, 2, 3, 4, 10, 51] {
+                let min_max = sliding_min_max(&numbers, window);
+                let medians = sliding_median(&numbers, window);
+                for (start, w) in numbers.windows(window).enumerate() {
+                    let mut sorted = w.to_vec();
+                    sorted.sort_unstable();
+                    assert_eq!((sorted[0], sorted[window - 1]), min_max[start]);
+                    assert_eq!(sorted[(window - 1) / 2], medians[start]);
+                }
+            }
+        }
+
+        assert!(sliding_min_max(&SAMPLE, 11).is_empty());
+        assert!(sliding_median(&SAMPLE, 0).is_empty());
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert!(check_input(INPUT).is_empty());
+
+        // blank lines and CRLF line endings are fine, like in `parse_depths`
+        let problems = check_input("199\n20a\n\n-3\r\n+200\n4294967296\n7\r\n");
+        assert_eq!(
+            vec![2, 4, 5, 6],
+            problems.iter().map(|p| p.line).collect::<Vec<_>>()
+        );
+        assert_eq!("line 5: column 1: unexpected '+'", problems[2].to_string());
+        assert!(parse_depths(b"199\n+200\n").is_err());
+    }
+}
@@ -0,0 +1,1614 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Up,
+    Down,
+    /// only in programs, undoes a `forward`
+    Back,
+}
+
+use aoc_2021::cache::{OrExit, ResultCache};
+use aoc_2021::check::{self, Problem};
+use aoc_2021::cli::{self, Args};
+use aoc_2021::params::{Param, Params};
+use rayon::prelude::*;
+use std::fmt::Write;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Direction::Forward => "forward",
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Back => "back",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A direction and its amount, as written in the input.
+pub type Command = (Direction, u32);
+
+const INPUT: &str = include_str!("input/day02.txt");
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "allow_negative_depth",
+        default: 0,
+        help: "1 lets the submarine go above the surface instead of failing",
+        choices: &[],
+    },
+    Param {
+        name: "lenient",
+        default: 0,
+        help: "1 skips malformed lines of a plain command list instead of rejecting the input",
+        choices: &[],
+    },
+    Param {
+        name: "program",
+        default: 0,
+        help: "1 reads the input as a program with repeat blocks, turns and comments",
+        choices: &[],
+    },
+    Param {
+        name: "plan_position",
+        default: 15,
+        help: "horizontal position the planned commands reach",
+        choices: &[],
+    },
+    Param {
+        name: "plan_depth",
+        default: 60,
+        help: "depth the planned commands reach",
+        choices: &[],
+    },
+    Param {
+        name: "plan_max_amount",
+        default: 9,
+        help: "largest amount of a single planned command",
+        choices: &[],
+    },
+    Param {
+        name: "plan_part",
+        default: 2,
+        help: "movement rules to plan for, 1 or 2",
+        choices: &[],
+    },
+];
+
+#[cfg(test)]
+fn parse_input(input: &str) -> Vec<Command> {
+    try_parse_input(input).unwrap_or_else(|errors| panic!("{}", errors[0]))
+}
+
+/// Strict: rejects the input if any line is malformed, reporting all of them.
+fn try_parse_input(input: &str) -> Result<Vec<Command>, Vec<ParseError>> {
+    match parse_input_lenient(input) {
+        (commands, errors) if errors.is_empty() => Ok(commands),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Lenient: the commands of all well-formed lines, and why every other line was skipped.
+fn parse_input_lenient(input: &str) -> (Vec<Command>, Vec<ParseError>) {
+    let mut commands = vec![];
+    let mut errors = vec![];
+    for (idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line2(line) {
+            Ok(command) => commands.push(command),
+            Err((column, kind)) => errors.push(ParseError {
+                line: idx + 1,
+                column,
+                kind,
+            }),
+        }
+    }
+
+    (commands, errors)
+}
+
+/// Reads a plain command list in the mode selected by the `lenient` param, printing the lines
+/// it skipped.
+fn read_commands(input: &str, lenient: bool) -> Result<Vec<Command>, Vec<ParseError>> {
+    if !lenient {
+        return try_parse_input(input);
+    }
+
+    let (commands, errors) = parse_input_lenient(input);
+    for err in &errors {
+        eprintln!("skipped {}", err);
+    }
+    Ok(commands)
+}
+
+fn exit_with_parse_errors(errors: &[ParseError], context: impl Display) -> ! {
+    for err in errors {
+        eprintln!("{}", err);
+    }
+    exit_with(format!("{} malformed line(s), {}", errors.len(), context))
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseErrorKind {
+    UnknownDirection,
+    ExpectedSpace,
+    ExpectedDigit,
+    Overflow,
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    /// 1-based
+    line: usize,
+    /// 1-based
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ParseErrorKind::UnknownDirection => "expected forward, up or down",
+            ParseErrorKind::ExpectedSpace => "expected a space after the direction",
+            ParseErrorKind::ExpectedDigit => "expected a digit",
+            ParseErrorKind::Overflow => "amount does not fit into a u32",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+pub fn main() {
+    let args = Args::from_env(&[
+        cli::Command::Solve,
+        cli::Command::Check,
+        cli::Command::Export,
+        cli::Command::Plan,
+    ]);
+    let input = args.input(INPUT);
+    let params = Params::from_args("day02", PARAMS, &args);
+    let allow_negative_depth = params.get("allow_negative_depth") != 0;
+    let lenient = params.get("lenient") != 0;
+    let is_program = params.get("program") != 0;
+    if is_program && lenient {
+        eprintln!("lenient only applies to plain command lists, not to programs");
+        std::process::exit(2);
+    }
+    match args.command {
+        cli::Command::Check => std::process::exit(check::report(&check_input(&input))),
+        cli::Command::Export => {
+            if is_program {
+                eprintln!("export needs a plain command list, not a program");
+                std::process::exit(2);
+            }
+            let commands = read_commands(&input, lenient).unwrap_or_else(|errors| {
+                exit_with_parse_errors(&errors, "export needs a plain command list")
+            });
+            let (part1, part2) =
+                trajectories(&commands, allow_negative_depth).unwrap_or_else(|err| exit_with(err));
+            let dir = args.output.clone().unwrap_or_else(|| PathBuf::from("."));
+            for (name, content) in [
+                ("trajectory.csv", to_csv(&commands, &part1, &part2)),
+                ("trajectory.svg", to_svg(&part1, &part2)),
+            ] {
+                let path = dir.join(name);
+                std::fs::write(&path, content).unwrap_or_else(|err| {
+                    exit_with(format!("could not write {}: {}", path.display(), err))
+                });
+                println!("wrote {}", path.display());
+            }
+            return;
+        }
+        cli::Command::Plan => {
+            let target = (params.get("plan_position"), params.get("plan_depth"));
+            let max_amount = u32::try_from(params.get("plan_max_amount"))
+                .unwrap_or_else(|_| exit_with("plan_max_amount does not fit into a u32"));
+            let plan = match params.get("plan_part") {
+                1 => DirectModel::plan(target, max_amount),
+                2 => AimModel::plan(target, max_amount),
+                part => exit_with(format!("plan_part must be 1 or 2, got {}", part)),
+            };
+            for (dir, count) in plan.unwrap_or_else(|err| exit_with(err)) {
+                println!("{} {}", dir, count);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let (commands, program) = if is_program {
+        let program = parse_program(&input).unwrap_or_else(|err| exit_with(err));
+        (vec![], Some(program))
+    } else {
+        let commands = read_commands(&input, lenient)
+            .unwrap_or_else(|errors| exit_with_parse_errors(&errors, "rejecting the input"));
+        (commands, None)
+    };
+    let implementation = if program.is_some() { "program" } else { "fold" };
+
+    let cache = ResultCache::new("day02", args.cache)
+        .or_exit()
+        .with_variant(&params);
+
+    let res: isize = cache
+        .solve("part1", implementation, input.as_bytes(), || {
+            let sum = match &program {
+                None => get_part1(commands.iter(), allow_negative_depth)
+                    .unwrap_or_else(|err| exit_with(err)),
+                Some(program) => run_program::<DirectModel>(program, allow_negative_depth)
+                    .map(|sub| (sub.position, sub.depth))
+                    .unwrap_or_else(|err| exit_with(err)),
+            };
+            sum.0 * sum.1
+        })
+        .or_exit();
+    println!("Part 1: {}", res);
+
+    let res: isize = cache
+        .solve("part2", implementation, input.as_bytes(), || {
+            let sum2 = match &program {
+                None => get_part2(commands.iter(), allow_negative_depth)
+                    .unwrap_or_else(|err| exit_with(err)),
+                Some(program) => run_program::<AimModel>(program, allow_negative_depth)
+                    .map(|sub| (sub.position, sub.depth, sub.aim))
+                    .unwrap_or_else(|err| exit_with(err)),
+            };
+            sum2.0 * sum2.1
+        })
+        .or_exit();
+    println!("Part 2: {}", res);
+}
+
+fn exit_with(err: impl Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
+
+/// The lines `try_parse_input` rejects, blank lines are fine.
+fn check_input(input: &str) -> Vec<Problem> {
+    let (_, errors) = parse_input_lenient(input);
+    errors
+        .iter()
+        .map(|err| Problem::new(err.line, format!("column {}: {}", err.column, err.kind)))
+        .collect()
+}
+
+#[cfg(test)]
+fn parse_line(line: &str) -> Command {
+    let split: Vec<&str> = line.split_whitespace().take(2).collect();
+    let dir = match split[0] {
+        "forward" => Direction::Forward,
+        "up" => Direction::Up,
+        "down" => Direction::Down,
+        _ => panic!("unsupported direction {}", split[0]),
+    };
+
+    let count: u32 = split[1].parse().unwrap();
+
+    (dir, count)
+}
+
+/// Byte based version of `parse_line`, errors are the 1-based column and what went wrong there.
+fn parse_line2(line: &str) -> Result<Command, (usize, ParseErrorKind)> {
+    let bytes = line.as_bytes();
+    // the first byte decides which word has to follow
+    let (dir, word): (_, &[u8]) = match bytes.first() {
+        Some(b'f') => (Direction::Forward, b"forward"),
+        Some(b'u') => (Direction::Up, b"up"),
+        Some(b'd') => (Direction::Down, b"down"),
+        _ => return Err((1, ParseErrorKind::UnknownDirection)),
+    };
+    if !bytes.starts_with(word) {
+        return Err((1, ParseErrorKind::UnknownDirection));
+    }
+    if bytes.get(word.len()) != Some(&b' ') {
+        return Err((word.len() + 1, ParseErrorKind::ExpectedSpace));
+    }
+
+    let start = word.len() + 1;
+    let digits = bytes[start..]
+        .strip_suffix(b"\r")
+        .unwrap_or(&bytes[start..]);
+    if digits.is_empty() {
+        return Err((start + 1, ParseErrorKind::ExpectedDigit));
+    }
+
+    let mut count: u32 = 0;
+    for (offset, &b) in digits.iter().enumerate() {
+        if !b.is_ascii_digit() {
+            return Err((start + offset + 1, ParseErrorKind::ExpectedDigit));
+        }
+        count = count
+            .checked_mul(10)
+            .and_then(|c| c.checked_add((b - b'0') as u32))
+            .ok_or((start + 1, ParseErrorKind::Overflow))?;
+    }
+
+    Ok((dir, count))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveErrorKind {
+    AboveSurface,
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveError {
+    /// 0-based index of the command that could not be applied
+    pub index: usize,
+    pub kind: MoveErrorKind,
+}
+
+impl Display for MoveErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            MoveErrorKind::AboveSurface => "takes the submarine above the surface",
+            MoveErrorKind::Overflow => "overflows the submarine state",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // 1-based, like the line and column of a parse error
+        write!(f, "command {}: {}", self.index + 1, self.kind)
+    }
+}
+
+/// The rules that decide how a command changes the submarine.
+///
+/// Models compute the new state with checked arithmetic, `Submarine::apply` discards it on error.
+trait MovementModel: Sized {
+    fn apply(sub: &mut Submarine<Self>, command: Command) -> Result<(), MoveErrorKind>;
+
+    /// Forward, up and down commands of at most `max_amount` that take a submarine from the
+    /// origin to the target (position, depth).
+    fn plan(target: (usize, usize), max_amount: u32) -> Result<Vec<Command>, PlanError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlanError {
+    ZeroMaxAmount,
+    /// the aim only turns into depth while moving forward
+    DepthWithoutPosition,
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::ZeroMaxAmount => write!(f, "commands need an amount of at least 1"),
+            PlanError::DepthWithoutPosition => {
+                write!(f, "depth can not be reached without moving forward")
+            }
+        }
+    }
+}
+
+/// Appends as few commands as possible that move `dir` by `total` in steps of `max_amount`.
+fn push_split(commands: &mut Vec<Command>, dir: Direction, total: usize, max_amount: u32) {
+    let max_amount = max_amount as usize;
+    commands.extend((0..total / max_amount).map(|_| (dir, max_amount as u32)));
+    let rest = total % max_amount;
+    if rest > 0 {
+        commands.push((dir, rest as u32));
+    }
+}
+
+/// Part 1: up and down change the depth directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct DirectModel;
+
+impl MovementModel for DirectModel {
+    fn apply(sub: &mut Submarine<Self>, (dir, count): Command) -> Result<(), MoveErrorKind> {
+        let count = isize::try_from(count).map_err(|_| MoveErrorKind::Overflow)?;
+        match dir {
+            Direction::Forward => sub.position = checked(sub.position.checked_add(count))?,
+            Direction::Up => sub.depth = sub.checked_depth(sub.depth.checked_sub(count))?,
+            Direction::Down => sub.depth = sub.checked_depth(sub.depth.checked_add(count))?,
+            Direction::Back => sub.position = checked(sub.position.checked_sub(count))?,
+        }
+        Ok(())
+    }
+
+    /// Optimal: every command covers as much of one axis as it may.
+    fn plan((position, depth): (usize, usize), max_amount: u32) -> Result<Vec<Command>, PlanError> {
+        if max_amount == 0 {
+            return Err(PlanError::ZeroMaxAmount);
+        }
+
+        let mut commands = vec![];
+        push_split(&mut commands, Direction::Forward, position, max_amount);
+        push_split(&mut commands, Direction::Down, depth, max_amount);
+        Ok(commands)
+    }
+}
+
+/// Part 2: up and down change the aim, forward moves along it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct AimModel;
+
+impl MovementModel for AimModel {
+    fn apply(sub: &mut Submarine<Self>, (dir, count): Command) -> Result<(), MoveErrorKind> {
+        let count = isize::try_from(count).map_err(|_| MoveErrorKind::Overflow)?;
+        match dir {
+            Direction::Forward => {
+                sub.position = checked(sub.position.checked_add(count))?;
+                sub.depth = sub.checked_depth(
+                    sub.aim
+                        .checked_mul(count)
+                        .and_then(|dive| sub.depth.checked_add(dive)),
+                )?;
+            }
+            Direction::Up => sub.aim = checked(sub.aim.checked_sub(count))?,
+            Direction::Down => sub.aim = checked(sub.aim.checked_add(count))?,
+            Direction::Back => {
+                sub.position = checked(sub.position.checked_sub(count))?;
+                sub.depth = sub.checked_depth(
+                    sub.aim
+                        .checked_mul(count)
+                        .and_then(|dive| sub.depth.checked_sub(dive)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// With `depth = q * position + r`, moves `position - r` forward with an aim of `q` and the
+    /// remaining `r` with an aim of `q + 1`.
+    ///
+    /// Any plan needs `position / max_amount` forward commands and enough down commands to reach
+    /// an aim of `depth / position` (both rounded up), this one has at most two commands more.
+    fn plan((position, depth): (usize, usize), max_amount: u32) -> Result<Vec<Command>, PlanError> {
+        if max_amount == 0 {
+            return Err(PlanError::ZeroMaxAmount);
+        }
+        if position == 0 {
+            return match depth {
+                0 => Ok(vec![]),
+                _ => Err(PlanError::DepthWithoutPosition),
+            };
+        }
+
+        let (aim, rest) = (depth / position, depth % position);
+        let mut commands = vec![];
+        push_split(&mut commands, Direction::Down, aim, max_amount);
+        push_split(
+            &mut commands,
+            Direction::Forward,
+            position - rest,
+            max_amount,
+        );
+        if rest > 0 {
+            commands.push((Direction::Down, 1));
+            push_split(&mut commands, Direction::Forward, rest, max_amount);
+        }
+        Ok(commands)
+    }
+}
+
+fn checked(value: Option<isize>) -> Result<isize, MoveErrorKind> {
+    value.ok_or(MoveErrorKind::Overflow)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Heading {
+    #[default]
+    East,
+    North,
+    West,
+    South,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    Left,
+    Right,
+}
+
+impl Heading {
+    fn turn(self, turn: Turn) -> Heading {
+        use Heading::*;
+        match (self, turn) {
+            (East, Turn::Left) | (West, Turn::Right) => North,
+            (North, Turn::Left) | (South, Turn::Right) => West,
+            (West, Turn::Left) | (East, Turn::Right) => South,
+            (South, Turn::Left) | (North, Turn::Right) => East,
+        }
+    }
+
+    fn unit(self) -> (isize, isize) {
+        match self {
+            Heading::East => (1, 0),
+            Heading::North => (0, 1),
+            Heading::West => (-1, 0),
+            Heading::South => (0, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Submarine<M> {
+    /// distance travelled forward, regardless of the heading
+    position: isize,
+    depth: isize,
+    /// only used by the `AimModel`
+    aim: isize,
+    /// where the forward movement took the submarine on the surface plane
+    x: isize,
+    y: isize,
+    heading: Heading,
+    /// number of commands applied so far
+    steps: usize,
+    allow_negative_depth: bool,
+    model: PhantomData<M>,
+}
+
+impl<M: MovementModel + Copy> Submarine<M> {
+    /// Lets the submarine go above the surface instead of failing.
+    fn allowing_negative_depth(mut self, allow: bool) -> Submarine<M> {
+        self.allow_negative_depth = allow;
+        self
+    }
+
+    fn apply(&mut self, command: Command) -> Result<(), MoveError> {
+        let mut next = *self;
+        M::apply(&mut next, command)
+            .and_then(|()| next.follow_heading(next.position - self.position))
+            .map_err(|kind| MoveError {
+                index: self.steps,
+                kind,
+            })?;
+        next.steps += 1;
+        *self = next;
+        Ok(())
+    }
+
+    fn turn(&mut self, turn: Turn) {
+        self.heading = self.heading.turn(turn);
+        self.steps += 1;
+    }
+
+    fn follow_heading(&mut self, distance: isize) -> Result<(), MoveErrorKind> {
+        let (dx, dy) = self.heading.unit();
+        self.x = checked(self.x.checked_add(distance * dx))?;
+        self.y = checked(self.y.checked_add(distance * dy))?;
+        Ok(())
+    }
+
+    /// Yields the state after every command, stops after the first error.
+    fn trajectory<I: IntoIterator<Item = Command>>(
+        self,
+        commands: I,
+    ) -> Trajectory<M, I::IntoIter> {
+        Trajectory {
+            sub: self,
+            commands: commands.into_iter(),
+            failed: false,
+        }
+    }
+
+    /// The state after all commands.
+    fn run<I: IntoIterator<Item = Command>>(self, commands: I) -> Result<Submarine<M>, MoveError> {
+        self.trajectory(commands).try_fold(self, |_, state| state)
+    }
+
+    fn checked_depth(&self, depth: Option<isize>) -> Result<isize, MoveErrorKind> {
+        match depth {
+            None => Err(MoveErrorKind::Overflow),
+            Some(depth) if depth < 0 && !self.allow_negative_depth => {
+                Err(MoveErrorKind::AboveSurface)
+            }
+            Some(depth) => Ok(depth),
+        }
+    }
+}
+
+struct Trajectory<M, I> {
+    sub: Submarine<M>,
+    commands: I,
+    failed: bool,
+}
+
+impl<M: MovementModel + Copy, I: Iterator<Item = Command>> Iterator for Trajectory<M, I> {
+    type Item = Result<Submarine<M>, MoveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let command = self.commands.next()?;
+        match self.sub.apply(command) {
+            Ok(()) => Some(Ok(self.sub)),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn get_part1<'a>(
+    inp: impl Iterator<Item = &'a Command>,
+    allow_negative_depth: bool,
+) -> Result<(isize, isize), MoveError> {
+    let sub = Submarine::<DirectModel>::default()
+        .allowing_negative_depth(allow_negative_depth)
+        .run(inp.copied())?;
+    Ok((sub.position, sub.depth))
+}
+
+pub fn get_part2<'a>(
+    inp: impl Iterator<Item = &'a Command>,
+    allow_negative_depth: bool,
+) -> Result<(isize, isize, isize), MoveError> {
+    let sub = Submarine::<AimModel>::default()
+        .allowing_negative_depth(allow_negative_depth)
+        .run(inp.copied())?;
+    Ok((sub.position, sub.depth, sub.aim))
+}
+
+// commands per rayon task, each chunk folds into one `AimTransform` and the chunks' transforms
+// are then composed one after another, so this keeps that second step short
+const PAR_CHUNK_SIZE: usize = 1 << 16;
+
+/// A sequence of part 2 commands as one affine map of (position, depth, aim):
+///
+/// ```text
+/// position' = position + forward
+/// depth'    = depth + dive + aim * forward
+/// aim'      = aim + turn
+/// ```
+///
+/// Maps of consecutive commands compose associatively, so they can be reduced in any grouping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AimTransform {
+    /// net forward movement, which is also the weight of the starting aim
+    pub forward: isize,
+    /// depth gained when starting with an aim of 0
+    pub dive: isize,
+    pub turn: isize,
+}
+
+impl AimTransform {
+    pub const IDENTITY: AimTransform = AimTransform {
+        forward: 0,
+        dive: 0,
+        turn: 0,
+    };
+
+    pub fn from_command((dir, count): Command) -> AimTransform {
+        let count = count as isize;
+        let (forward, turn) = match dir {
+            Direction::Forward => (count, 0),
+            Direction::Back => (-count, 0),
+            Direction::Up => (0, -count),
+            Direction::Down => (0, count),
+        };
+        AimTransform {
+            forward,
+            dive: 0,
+            turn,
+        }
+    }
+
+    /// `self` followed by `next`, `None` on overflow.
+    pub fn then(self, next: AimTransform) -> Option<AimTransform> {
+        // `next` starts with the aim `self` left behind
+        let dive = self
+            .turn
+            .checked_mul(next.forward)?
+            .checked_add(next.dive)?
+            .checked_add(self.dive)?;
+        Some(AimTransform {
+            forward: self.forward.checked_add(next.forward)?,
+            dive,
+            turn: self.turn.checked_add(next.turn)?,
+        })
+    }
+}
+
+/// Same as `get_part2`, but composes the commands as `AimTransform`s in parallel.
+///
+/// Returns `None` if the arithmetic overflows. The composed transforms only describe the final
+/// state, so the depths in between are never checked, like `get_part2` with
+/// `allow_negative_depth`.
+pub fn get_part2_par(commands: &[Command]) -> Option<(isize, isize, isize)> {
+    let transform = commands
+        .par_chunks(PAR_CHUNK_SIZE)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .try_fold(AimTransform::IDENTITY, |acc, &command| {
+                    acc.then(AimTransform::from_command(command))
+                })
+        })
+        .reduce(
+            || Some(AimTransform::IDENTITY),
+            |a, b| a.and_then(|a| a.then(b?)),
+        )?;
+
+    // the submarine starts at the origin, so the map's constants are the final state
+    Some((transform.forward, transform.dive, transform.turn))
+}
+
+/// The states after every command under the part 1 and part 2 models.
+type Trajectories = (Vec<Submarine<DirectModel>>, Vec<Submarine<AimModel>>);
+
+fn trajectories(
+    commands: &[Command],
+    allow_negative_depth: bool,
+) -> Result<Trajectories, MoveError> {
+    let part1 = Submarine::default()
+        .allowing_negative_depth(allow_negative_depth)
+        .trajectory(commands.iter().copied())
+        .collect::<Result<_, _>>()?;
+    let part2 = Submarine::default()
+        .allowing_negative_depth(allow_negative_depth)
+        .trajectory(commands.iter().copied())
+        .collect::<Result<_, _>>()?;
+    Ok((part1, part2))
+}
+
+/// One row per command, with the state after it under both models.
+fn to_csv(
+    commands: &[Command],
+    part1: &[Submarine<DirectModel>],
+    part2: &[Submarine<AimModel>],
+) -> String {
+    let mut out = String::from(
+        "step,command,amount,part1_position,part1_depth,part2_position,part2_depth,part2_aim\n",
+    );
+    for (idx, ((dir, count), (p1, p2))) in commands.iter().zip(part1.iter().zip(part2)).enumerate()
+    {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            idx + 1,
+            dir,
+            count,
+            p1.position,
+            p1.depth,
+            p2.position,
+            p2.depth,
+            p2.aim
+        )
+        .unwrap();
+    }
+    out
+}
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 400.0;
+const SVG_MARGIN: f64 = 70.0;
+
+/// Depth over horizontal position for both models.
+///
+/// Part 2 goes orders of magnitude deeper than part 1, so each path gets its own depth axis
+/// (part 1 on the left, part 2 on the right), with depth growing downwards.
+fn to_svg(part1: &[Submarine<DirectModel>], part2: &[Submarine<AimModel>]) -> String {
+    let (left, right) = (SVG_MARGIN, SVG_WIDTH - SVG_MARGIN);
+    let (top, bottom) = (SVG_MARGIN, SVG_HEIGHT - SVG_MARGIN);
+
+    // both models move forward the same way, the paths start at the origin
+    let positions = part1.iter().map(|sub| sub.position).chain([0]);
+    let (min_x, max_x) = (positions.clone().min().unwrap(), positions.max().unwrap());
+    let depth_range = |depths: Vec<isize>| {
+        let min = depths.iter().copied().chain([0]).min().unwrap();
+        let max = depths.iter().copied().chain([0]).max().unwrap();
+        (min, max)
+    };
+    let (min1, max1) = depth_range(part1.iter().map(|sub| sub.depth).collect());
+    let (min2, max2) = depth_range(part2.iter().map(|sub| sub.depth).collect());
+
+    let path = |points: Vec<(isize, isize)>, (min_y, max_y): (isize, isize)| {
+        [(0, 0)]
+            .iter()
+            .chain(&points)
+            .map(|&(x, y)| {
+                format!(
+                    "{:.1},{:.1}",
+                    scale(x, min_x, max_x, left, right),
+                    scale(y, min_y, max_y, top, bottom)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+        w = SVG_WIDTH,
+        h = SVG_HEIGHT
+    )
+    .unwrap();
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    // axes: horizontal position along the bottom, one depth axis per model
+    writeln!(
+        out,
+        r#"<path d="M{l},{t} V{b} H{r} V{t}" fill="none" stroke="black"/>"#,
+        l = left,
+        r = right,
+        t = top,
+        b = bottom
+    )
+    .unwrap();
+    for (text, x, y, anchor) in [
+        (min_x.to_string(), left, bottom + 15.0, "middle"),
+        (max_x.to_string(), right, bottom + 15.0, "middle"),
+        (
+            "horizontal position".to_string(),
+            (left + right) / 2.0,
+            bottom + 35.0,
+            "middle",
+        ),
+        (min1.to_string(), left - 5.0, top + 4.0, "end"),
+        (max1.to_string(), left - 5.0, bottom, "end"),
+        ("part 1 depth".to_string(), left, top - 10.0, "middle"),
+        (min2.to_string(), right + 5.0, top + 4.0, "start"),
+        (max2.to_string(), right + 5.0, bottom, "start"),
+        ("part 2 depth".to_string(), right, top - 10.0, "middle"),
+    ] {
+        writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="{}">{}</text>"#,
+            x, y, anchor, text
+        )
+        .unwrap();
+    }
+
+    for (points, range, color) in [
+        (
+            part1.iter().map(|sub| (sub.position, sub.depth)).collect(),
+            (min1, max1),
+            "steelblue",
+        ),
+        (
+            part2.iter().map(|sub| (sub.position, sub.depth)).collect(),
+            (min2, max2),
+            "firebrick",
+        ),
+    ] {
+        writeln!(
+            out,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+            path(points, range),
+            color
+        )
+        .unwrap();
+    }
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+/// Maps `value` from `min..=max` linearly onto `from..=to`.
+fn scale(value: isize, min: isize, max: isize, from: f64, to: f64) -> f64 {
+    if max == min {
+        return from;
+    }
+    from + (value - min) as f64 / (max - min) as f64 * (to - from)
+}
+
+/// An error in a program, with the 1-based position it was found at.
+#[derive(Debug, PartialEq)]
+struct ProgramError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ProgramError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> ProgramError {
+        ProgramError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ProgramError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind<'a> {
+    Word(&'a str),
+    Number(u32),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Token<'a> {
+    kind: TokenKind<'a>,
+    line: usize,
+    column: usize,
+}
+
+/// Splits a program into words, numbers and braces, dropping whitespace and `#` comments.
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>, ProgramError> {
+    let mut tokens = vec![];
+    for (idx, text) in source.lines().enumerate() {
+        let bytes = text.as_bytes();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let start = pos;
+            let (line, column) = (idx + 1, start + 1);
+            let kind = match bytes[pos] {
+                b'#' => break,
+                b if b.is_ascii_whitespace() => {
+                    pos += 1;
+                    continue;
+                }
+                b'{' => {
+                    pos += 1;
+                    TokenKind::Open
+                }
+                b'}' => {
+                    pos += 1;
+                    TokenKind::Close
+                }
+                b if b.is_ascii_digit() => {
+                    while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                    let number = text[start..pos].parse().map_err(|_| {
+                        ProgramError::new(line, column, "number does not fit into a u32")
+                    })?;
+                    TokenKind::Number(number)
+                }
+                b if b.is_ascii_alphabetic() => {
+                    while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+                        pos += 1;
+                    }
+                    TokenKind::Word(&text[start..pos])
+                }
+                _ => {
+                    let c = text[start..].chars().next().unwrap();
+                    return Err(ProgramError::new(
+                        line,
+                        column,
+                        format!("unexpected {:?}", c),
+                    ));
+                }
+            };
+            tokens.push(Token { kind, line, column });
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StatementKind {
+    Move(Command),
+    Turn(Turn),
+    Repeat(u32, Vec<Statement>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    kind: StatementKind,
+    line: usize,
+    column: usize,
+}
+
+/// Parses a submarine program:
+///
+/// ```text
+/// # comments run to the end of the line
+/// forward 5           # also back, up and down
+/// turn left           # or right, changes where forward and back go on the surface
+/// repeat 3 { down 1 forward 2 }
+/// ```
+///
+/// Every puzzle input is a valid program.
+fn parse_program(source: &str) -> Result<Vec<Statement>, ProgramError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        end: (source.lines().count().max(1), 1),
+    };
+    parser.block(None)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    /// where running out of tokens is reported
+    end: (usize, usize),
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    fn error_at(&self, token: Option<Token>, message: impl Into<String>) -> ProgramError {
+        let (line, column) = token.map_or(self.end, |t| (t.line, t.column));
+        ProgramError::new(line, column, message)
+    }
+
+    /// Statements up to the `}` matching `open`, or up to the end of the program.
+    fn block(&mut self, open: Option<Token>) -> Result<Vec<Statement>, ProgramError> {
+        let mut statements = vec![];
+        loop {
+            let token = self.next();
+            match token.map(|t| t.kind) {
+                None => match open {
+                    None => return Ok(statements),
+                    Some(open) => return Err(self.error_at(Some(open), "unclosed `{`")),
+                },
+                Some(TokenKind::Close) if open.is_some() => return Ok(statements),
+                Some(TokenKind::Close) => return Err(self.error_at(token, "unexpected `}`")),
+                Some(TokenKind::Word(word)) => {
+                    statements.push(self.statement(word, token.unwrap())?)
+                }
+                Some(_) => return Err(self.error_at(token, "expected a command")),
+            }
+        }
+    }
+
+    fn statement(&mut self, word: &str, start: Token) -> Result<Statement, ProgramError> {
+        let kind = match word {
+            "forward" => StatementKind::Move((Direction::Forward, self.number()?)),
+            "back" => StatementKind::Move((Direction::Back, self.number()?)),
+            "up" => StatementKind::Move((Direction::Up, self.number()?)),
+            "down" => StatementKind::Move((Direction::Down, self.number()?)),
+            "turn" => {
+                let token = self.next();
+                match token.map(|t| t.kind) {
+                    Some(TokenKind::Word("left")) => StatementKind::Turn(Turn::Left),
+                    Some(TokenKind::Word("right")) => StatementKind::Turn(Turn::Right),
+                    _ => return Err(self.error_at(token, "expected `left` or `right`")),
+                }
+            }
+            "repeat" => {
+                let count = self.number()?;
+                let open = self.next();
+                if open.map(|t| t.kind) != Some(TokenKind::Open) {
+                    return Err(self.error_at(open, "expected `{`"));
+                }
+                StatementKind::Repeat(count, self.block(open)?)
+            }
+            _ => {
+                return Err(self.error_at(Some(start), format!("unknown command {:?}", word)));
+            }
+        };
+
+        Ok(Statement {
+            kind,
+            line: start.line,
+            column: start.column,
+        })
+    }
+
+    fn number(&mut self) -> Result<u32, ProgramError> {
+        let token = self.next();
+        match token.map(|t| t.kind) {
+            Some(TokenKind::Number(number)) => Ok(number),
+            _ => Err(self.error_at(token, "expected a number")),
+        }
+    }
+}
+
+/// Runs a program with the rules of `M`, errors point at the statement that failed.
+fn run_program<M: MovementModel + Copy + Default>(
+    program: &[Statement],
+    allow_negative_depth: bool,
+) -> Result<Submarine<M>, ProgramError> {
+    let mut sub = Submarine::<M>::default().allowing_negative_depth(allow_negative_depth);
+    execute(program, &mut sub)?;
+    Ok(sub)
+}
+
+fn execute<M: MovementModel + Copy>(
+    statements: &[Statement],
+    sub: &mut Submarine<M>,
+) -> Result<(), ProgramError> {
+    for statement in statements {
+        match &statement.kind {
+            // the step index counts executed commands, the statement position is more useful
+            StatementKind::Move(command) => sub.apply(*command).map_err(|err| {
+                ProgramError::new(statement.line, statement.column, err.kind.to_string())
+            })?,
+            StatementKind::Turn(turn) => sub.turn(*turn),
+            StatementKind::Repeat(count, body) => {
+                for _ in 0..*count {
+                    execute(body, sub)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::xorshift::XorShift;
+
+    #[test]
+    fn test_day02_part1() {
+        let p1_res = get_part1(parse_input(INPUT).iter(), false).unwrap();
+        assert_eq!(2073315, p1_res.0 * p1_res.1);
+    }
+
+    #[test]
+    fn test_day02_part2() {
+        let p2_res = get_part2(parse_input(INPUT).iter(), false).unwrap();
+        assert_eq!(1840311528, p2_res.0 * p2_res.1);
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert!(check_input(INPUT).is_empty());
+
+        let problems = check_input("forward 5\nsideways 2\nup -3\n\ndown\nback x\n");
+        assert_eq!(
+            vec![2, 3, 5, 6],
+            problems.iter().map(|p| p.line).collect::<Vec<_>>()
+        );
+
+        // exactly what the strict parser rejects
+        let input = "forward +5\ndown\t3\nup 5000000000\n";
+        assert_eq!(
+            vec![1, 2, 3],
+            check_input(input)
+                .iter()
+                .map(|p| p.line)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(3, try_parse_input(input).unwrap_err().len());
+        assert_eq!(
+            "line 3: column 4: amount does not fit into a u32",
+            check_input(input)[2].to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_line2_matches_parse_line() {
+        for line in INPUT.lines().filter(|line| !line.trim().is_empty()) {
+            assert_eq!(parse_line(line), parse_line2(line).unwrap());
+        }
+
+        for line in [
+            "forward 12",
+            "down 0",
+            "up 007",
+            "up 4294967295",
+            "down 123456\r",
+        ] {
+            assert_eq!(parse_line(line), parse_line2(line).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use ParseErrorKind::*;
+        assert_eq!(Err((1, UnknownDirection)), parse_line2("sideways 2"));
+        assert_eq!(Err((1, UnknownDirection)), parse_line2("fwd 2"));
+        assert_eq!(Err((3, ExpectedSpace)), parse_line2("up2"));
+        assert_eq!(Err((6, ExpectedDigit)), parse_line2("down "));
+        assert_eq!(Err((7, ExpectedDigit)), parse_line2("down 1x"));
+        assert_eq!(Err((4, Overflow)), parse_line2("up 4294967296"));
+
+        assert_eq!(
+            Err(vec![ParseError {
+                line: 3,
+                column: 9,
+                kind: ExpectedDigit
+            }]),
+            try_parse_input("forward 5\n\nforward -1\n")
+        );
+        assert_eq!(
+            vec![(Direction::Forward, 15), (Direction::Down, 120)],
+            try_parse_input("forward 15\n\ndown 120\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_collect_all_errors() {
+        use ParseErrorKind::*;
+        let input = "forward 5\nforward\nsideways 2\n\ndown 3\nup 99999999999\nup 1 # rise\n";
+        let expected = vec![
+            (2, 8, ExpectedSpace),
+            (3, 1, UnknownDirection),
+            (6, 4, Overflow),
+            (7, 5, ExpectedDigit),
+        ];
+
+        let (commands, errors) = parse_input_lenient(input);
+        assert_eq!(
+            vec![(Direction::Forward, 5), (Direction::Down, 3)],
+            commands
+        );
+        assert_eq!(
+            expected,
+            errors
+                .into_iter()
+                .map(|err| (err.line, err.column, err.kind))
+                .collect::<Vec<_>>()
+        );
+
+        let errors = try_parse_input(input).unwrap_err();
+        assert_eq!(4, errors.len());
+        assert_eq!("line 7, column 5: expected a digit", errors[3].to_string());
+        assert_eq!(Ok(parse_input(INPUT)), try_parse_input(INPUT));
+    }
+
+    #[test]
+    fn test_corrupted_log_is_rejected() {
+        // split and joined lines still read as a program, so logs must never fall back to one
+        let input = "forward 5\nforward\n3\ndown 2 up 1\n";
+        let errors = read_commands(input, false).unwrap_err();
+        assert_eq!(
+            vec![(2, 8), (3, 1), (4, 7)],
+            errors
+                .iter()
+                .map(|err| (err.line, err.column))
+                .collect::<Vec<_>>()
+        );
+        assert!(parse_program(input).is_ok());
+    }
+
+    #[test]
+    fn test_part2() {
+        use Direction::*;
+        let input = vec![
+            (Forward, 5),
+            (Down, 5),
+            (Forward, 8),
+            (Up, 3),
+            (Down, 8),
+            (Forward, 2),
+        ];
+
+        let res = get_part2(input.iter(), false).unwrap();
+
+        assert_eq!((15, 60, 10), res);
+    }
+
+    #[test]
+    fn test_submarine_trajectory() {
+        use Direction::*;
+        let commands = [(Forward, 5), (Down, 5), (Forward, 8), (Up, 3)];
+
+        let direct: Vec<_> = Submarine::<DirectModel>::default()
+            .trajectory(commands)
+            .map(|sub| sub.map(|sub| (sub.position, sub.depth)).unwrap())
+            .collect();
+        assert_eq!(vec![(5, 0), (5, 5), (13, 5), (13, 2)], direct);
+
+        let aimed: Vec<_> = Submarine::<AimModel>::default()
+            .trajectory(commands)
+            .map(|sub| sub.map(|sub| (sub.position, sub.depth, sub.aim)).unwrap())
+            .collect();
+        assert_eq!(vec![(5, 0, 0), (5, 0, 5), (13, 40, 5), (13, 40, 2)], aimed);
+
+        let mut sub = Submarine::<AimModel>::default();
+        sub.apply((Down, 2)).unwrap();
+        sub.apply((Forward, 3)).unwrap();
+        assert_eq!((3, 6, 2), (sub.position, sub.depth, sub.aim));
+        assert_eq!(Ok(sub), sub.run([]));
+    }
+
+    #[test]
+    fn test_puzzle_input_is_a_program() {
+        let program = parse_program(INPUT).unwrap();
+        let commands = parse_input(INPUT);
+        assert_eq!(commands.len(), program.len());
+
+        let direct = run_program::<DirectModel>(&program, false).unwrap();
+        assert_eq!(
+            get_part1(commands.iter(), false).unwrap(),
+            (direct.position, direct.depth)
+        );
+        let aimed = run_program::<AimModel>(&program, false).unwrap();
+        assert_eq!(
+            get_part2(commands.iter(), false).unwrap(),
+            (aimed.position, aimed.depth, aimed.aim)
+        );
+        // without turns the submarine stays on the x axis
+        assert_eq!((aimed.position, 0), (aimed.x, aimed.y));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        use TokenKind::*;
+        let tokens = tokenize("repeat 2 {\n  up 10 # rise\n}").unwrap();
+        assert_eq!(
+            vec![
+                (Word("repeat"), 1, 1),
+                (Number(2), 1, 8),
+                (Open, 1, 10),
+                (Word("up"), 2, 3),
+                (Number(10), 2, 6),
+                (Close, 3, 1),
+            ],
+            tokens
+                .iter()
+                .map(|t| (t.kind, t.line, t.column))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_program() {
+        let source = "
+            # a square with a dive on every side
+            down 1
+            repeat 4 {
+                forward 3
+                turn left
+            }
+            back 2
+        ";
+        let program = parse_program(source).unwrap();
+
+        let direct = run_program::<DirectModel>(&program, false).unwrap();
+        assert_eq!((10, 1), (direct.position, direct.depth));
+        // back 2 goes against the heading, which is east again
+        assert_eq!((-2, 0, Heading::East), (direct.x, direct.y, direct.heading));
+
+        let aimed = run_program::<AimModel>(&program, false).unwrap();
+        assert_eq!((10, 10, 1), (aimed.position, aimed.depth, aimed.aim));
+
+        let nested = parse_program("repeat 2 { repeat 3 { forward 1 } down 1 }").unwrap();
+        let aimed = run_program::<AimModel>(&nested, false).unwrap();
+        assert_eq!((6, 3, 2), (aimed.position, aimed.depth, aimed.aim));
+    }
+
+    #[test]
+    fn test_program_errors() {
+        let error = |source| {
+            let err = parse_program(source).unwrap_err();
+            (err.line, err.column, err.message)
+        };
+        assert_eq!((1, 9, "expected a number".to_string()), error("forward x"));
+        assert_eq!(
+            (2, 1, "unknown command \"sideways\"".to_string()),
+            error("up 1\nsideways 2")
+        );
+        assert_eq!(
+            (1, 6, "expected `left` or `right`".to_string()),
+            error("turn around")
+        );
+        assert_eq!(
+            (1, 10, "unclosed `{`".to_string()),
+            error("repeat 2 { up 1")
+        );
+        assert_eq!((1, 6, "unexpected `}`".to_string()), error("up 1 }"));
+        assert_eq!((1, 6, "unexpected '-'".to_string()), error("down -1"));
+        assert_eq!(
+            (1, 4, "number does not fit into a u32".to_string()),
+            error("up 4294967296")
+        );
+
+        let program = parse_program("down 1\nrepeat 3 {\n  up 1\n}").unwrap();
+        assert_eq!(
+            Err(ProgramError::new(
+                3,
+                3,
+                "takes the submarine above the surface"
+            )),
+            run_program::<DirectModel>(&program, false)
+        );
+    }
+
+    #[test]
+    fn test_trajectory_export() {
+        use Direction::*;
+        let commands = [(Forward, 5), (Down, 5), (Forward, 8), (Up, 3)];
+        let (part1, part2) = trajectories(&commands, false).unwrap();
+
+        let csv = to_csv(&commands, &part1, &part2);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(5, lines.len());
+        assert_eq!(
+            "step,command,amount,part1_position,part1_depth,part2_position,part2_depth,part2_aim",
+            lines[0]
+        );
+        assert_eq!("3,forward,8,13,5,13,40,5", lines[3]);
+
+        let svg = to_svg(&part1, &part2);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(2, svg.matches("<polyline").count());
+        // part 2 reaches the bottom of its own axis at the end of the path
+        assert!(svg.contains(&format!(
+            "{:.1},{:.1}\"",
+            SVG_WIDTH - SVG_MARGIN,
+            SVG_HEIGHT - SVG_MARGIN
+        )));
+
+        let (part1, _) = trajectories(&parse_input(INPUT), false).unwrap();
+        let last = part1.last().unwrap();
+        assert_eq!(2073315, last.position * last.depth);
+    }
+
+    fn random_commands(len: usize, seed: u64) -> Vec<Command> {
+        XorShift::new(seed)
+            .take(len)
+            .map(|n| {
+                let dir = match n % 7 {
+                    0..=2 => Direction::Forward,
+                    3 => Direction::Back,
+                    4 => Direction::Up,
+                    _ => Direction::Down,
+                };
+                (dir, (n >> 8) as u32 % 10)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aim_transform_composes() {
+        use Direction::*;
+        let sample = [
+            (Forward, 5),
+            (Down, 5),
+            (Forward, 8),
+            (Up, 3),
+            (Down, 8),
+            (Forward, 2),
+        ];
+        let transforms: Vec<_> = sample
+            .iter()
+            .map(|&c| AimTransform::from_command(c))
+            .collect();
+        let left = transforms
+            .iter()
+            .try_fold(AimTransform::IDENTITY, |acc, &t| acc.then(t))
+            .unwrap();
+        let right = transforms[..3]
+            .iter()
+            .try_fold(AimTransform::IDENTITY, |acc, &t| acc.then(t))
+            .unwrap()
+            .then(
+                transforms[3..]
+                    .iter()
+                    .try_fold(AimTransform::IDENTITY, |acc, &t| acc.then(t))
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(left, right);
+        assert_eq!(Some((15, 60, 10)), get_part2_par(&sample));
+
+        let overflow = [(Down, u32::MAX), (Forward, u32::MAX)];
+        assert_eq!(None, get_part2_par(&overflow));
+    }
+
+    #[test]
+    fn test_part2_par_matches_sequential() {
+        let commands = parse_input(INPUT);
+        assert_eq!(
+            get_part2(commands.iter(), false).ok(),
+            get_part2_par(&commands)
+        );
+
+        // long enough to be split over several chunks
+        for (len, seed) in [(0, 1), (1, 2), (1000, 3), (3 * PAR_CHUNK_SIZE + 17, 4)] {
+            let commands = random_commands(len, seed);
+            assert_eq!(
+                get_part2(commands.iter(), true).ok(),
+                get_part2_par(&commands)
+            );
+        }
+    }
+
+    #[test]
+    fn test_plan_replays_to_target() {
+        assert_eq!(
+            Ok(vec![(Direction::Down, 4), (Direction::Forward, 15)]),
+            AimModel::plan((15, 60), 20)
+        );
+
+        for n in XorShift::new(42).take(500) {
+            let position = (n % 2000) as usize;
+            let depth = if position == 0 {
+                0
+            } else {
+                (n >> 16) as usize % 20_000
+            };
+            let max_amount = (n >> 40) as u32 % 20 + 1;
+
+            let plan = AimModel::plan((position, depth), max_amount).unwrap();
+            assert!(plan
+                .iter()
+                .all(|&(_, count)| (1..=max_amount).contains(&count)));
+            let (h, d, _) = get_part2(plan.iter(), false).unwrap();
+            assert_eq!((position as isize, depth as isize), (h, d));
+
+            // commands needed to cover `total` in steps of `max_amount`
+            let bound = |total: usize| (0..total).step_by(max_amount as usize).count();
+            let min_aim = (0..).find(|aim| aim * position >= depth).unwrap();
+            assert!(plan.len() <= bound(position) + bound(min_aim) + 2);
+
+            let plan = DirectModel::plan((position, depth), max_amount).unwrap();
+            let (h, d) = get_part1(plan.iter(), false).unwrap();
+            assert_eq!((position as isize, depth as isize), (h, d));
+            assert_eq!(bound(position) + bound(depth), plan.len());
+        }
+
+        assert_eq!(
+            Err(PlanError::DepthWithoutPosition),
+            AimModel::plan((0, 3), 9)
+        );
+        assert_eq!(Ok(vec![]), AimModel::plan((0, 0), 9));
+        assert_eq!(Err(PlanError::ZeroMaxAmount), DirectModel::plan((1, 1), 0));
+    }
+
+    #[test]
+    fn test_checked_movement() {
+        use Direction::*;
+        let above = [(Down, 2), (Up, 1), (Up, 2), (Down, 5)];
+        assert_eq!(
+            Err(MoveError {
+                index: 2,
+                kind: MoveErrorKind::AboveSurface
+            }),
+            get_part1(above.iter(), false)
+        );
+        assert_eq!(
+            "command 3: takes the submarine above the surface",
+            get_part1(above.iter(), false).unwrap_err().to_string()
+        );
+        assert_eq!(Ok((0, 4)), get_part1(above.iter(), true));
+
+        // a negative aim only matters once the submarine moves forward
+        let aimed_up = [(Up, 3), (Down, 1), (Forward, 2)];
+        assert_eq!(
+            Err(MoveError {
+                index: 2,
+                kind: MoveErrorKind::AboveSurface
+            }),
+            get_part2(aimed_up.iter(), false)
+        );
+        assert_eq!(Ok((2, -4, -2)), get_part2(aimed_up.iter(), true));
+
+        // aim * count already exceeds isize::MAX on the first forward
+        let steep = [(Down, u32::MAX), (Forward, u32::MAX)];
+        assert_eq!(
+            Err(MoveError {
+                index: 1,
+                kind: MoveErrorKind::Overflow
+            }),
+            get_part2(steep.iter(), false)
+        );
+
+        // the trajectory ends with the error, the state before it is kept
+        let mut trajectory = Submarine::<DirectModel>::default().trajectory(above);
+        assert_eq!(2, trajectory.by_ref().filter(Result::is_ok).count());
+        assert_eq!(None, trajectory.next());
+    }
+}
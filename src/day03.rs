@@ -0,0 +1,1280 @@
+use aoc_2021::cache::{OrExit, ResultCache};
+use aoc_2021::check::{self, Problem};
+use aoc_2021::cli::{Args, Command};
+use aoc_2021::params::{Param, Params};
+use rayon::prelude::*;
+use std::fmt::{Binary, Debug, Display, Formatter};
+use std::ops::Range;
+use std::simd::{LaneCount, Simd, SupportedLaneCount};
+
+// values are stored as u32 or u64 (see `Word`), and counted in up to 64 simd lanes
+const MAX_LINE_WIDTH: usize = 64;
+const INPUT: &str = include_str!("input/day03.txt");
+const PARAMS: &[Param] = &[
+    Param {
+        name: "line_width",
+        default: 0,
+        help: "number of digits per line (at most 64), 0 detects it from the input",
+        choices: &[],
+    },
+    Param {
+        name: "num_lines",
+        default: 0,
+        help: "number of lines in the input, 0 detects it from the input",
+        choices: &[],
+    },
+    Param {
+        name: "oxygen_ties",
+        default: 1,
+        help: "kept by the oxygen rating on a tied column: prefer_zero, prefer_one or error",
+        choices: TieBreak::NAMES,
+    },
+    Param {
+        name: "co2_ties",
+        default: 0,
+        help: "kept by the co2 rating on a tied column: prefer_zero, prefer_one or error",
+        choices: TieBreak::NAMES,
+    },
+    Param {
+        name: "trie",
+        default: 0,
+        help: "1 walks a binary trie for part 2 instead of searching the sorted numbers",
+        choices: &[],
+    },
+];
+
+pub fn main() {
+    let args = Args::from_env(&[Command::Solve, Command::Check]);
+    let input = args.input(INPUT);
+    let params = Params::from_args("day03", PARAMS, &args);
+    let detected = Dimensions::detect(&input);
+    // non-zero params override what was detected
+    let (line_width, num_lines) = match (params.get("line_width"), params.get("num_lines")) {
+        (0, 0) => (detected.line_width, detected.num_lines),
+        (0, lines) => (detected.line_width, lines),
+        (width, 0) => (width, Dimensions::count_lines(&input, width)),
+        (width, lines) => (width, lines),
+    };
+    if line_width == 0 || line_width > MAX_LINE_WIDTH {
+        eprintln!("line_width must be between 1 and {}", MAX_LINE_WIDTH);
+        std::process::exit(2);
+    }
+    if args.command == Command::Check {
+        std::process::exit(check::report(&check_input(&input, line_width, num_lines)));
+    }
+    // the kernels read the input with a fixed stride, so every line needs the same width
+    let problems = check_lines(&input, line_width);
+    if !problems.is_empty() {
+        check::report(&problems);
+        std::process::exit(2);
+    }
+    let available_lines = Dimensions::count_lines(&input, line_width);
+    if num_lines > available_lines {
+        eprintln!("num_lines must be at most {}", available_lines);
+        std::process::exit(2);
+    }
+
+    let ties = TieBreaks {
+        oxygen: TieBreak::from_param(params.get("oxygen_ties")),
+        co2: TieBreak::from_param(params.get("co2_ties")),
+    };
+
+    let cache = ResultCache::new("day03", args.cache)
+        .or_exit()
+        .with_variant(&params);
+
+    // the product of two 64 bit wide numbers needs 128 bits
+    let res: u128 = cache
+        .solve("part1", "simd", input.as_bytes(), || {
+            let res = add_lines_simd(&input, num_lines, line_width);
+            res.gamma as u128 * res.epsilon as u128
+        })
+        .or_exit();
+    println!("part1: {}", res);
+
+    let (implementation, step_two): (_, fn(&str, usize, TieBreaks) -> _) = match params.get("trie")
+    {
+        0 => ("sort", try_step_two),
+        _ => ("trie", try_step_two_trie),
+    };
+    let res: u128 = cache
+        .solve("part2", implementation, input.as_bytes(), || {
+            let step2 = step_two(&input, line_width, ties).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            });
+            step2.oxygen as u128 * step2.co2 as u128
+        })
+        .or_exit();
+    println!("part2: {}", res);
+}
+
+/// The shape of the diagnostic report, every line has the same width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dimensions {
+    pub line_width: usize,
+    pub num_lines: usize,
+}
+
+impl Dimensions {
+    /// Only looks at the first line, the line count follows from the input length.
+    pub fn detect(input: &str) -> Dimensions {
+        // `lines` also strips the `\r` of a CRLF line ending
+        let line_width = input.lines().next().map_or(0, str::len);
+        Dimensions {
+            line_width,
+            num_lines: Dimensions::count_lines(input, line_width),
+        }
+    }
+
+    /// Lines of `line_width` digits in `input`, with or without a final (or extra) newline.
+    pub fn count_lines(input: &str, line_width: usize) -> usize {
+        if input.is_empty() {
+            return 0;
+        }
+        let row_len = Dimensions::row_len(input.as_bytes(), line_width);
+        // the last line does not need a line ending
+        (input.len() + row_len - line_width) / row_len
+    }
+
+    /// Bytes from the start of one line to the start of the next, for LF or CRLF line endings.
+    pub fn row_len(input: &[u8], line_width: usize) -> usize {
+        match input.get(line_width) {
+            Some(b'\r') => line_width + 2,
+            _ => line_width + 1,
+        }
+    }
+}
+
+/// The lines of the report, without the empty lines an extra newline leaves at the end, which
+/// `Dimensions::detect` accepts as well.
+fn report_lines(input: &str) -> std::str::Lines<'_> {
+    input.trim_end_matches(['\n', '\r']).lines()
+}
+
+pub fn check_input(input: &str, line_width: usize, num_lines: usize) -> Vec<Problem> {
+    let mut problems = check_lines(input, line_width);
+    let line_count = report_lines(input).count();
+    if line_count != num_lines {
+        problems.push(Problem::new(
+            line_count,
+            format!("expected {} lines, got {}", num_lines, line_count),
+        ));
+    }
+
+    problems
+}
+
+/// Lines that are not `line_width` binary digits.
+fn check_lines(input: &str, line_width: usize) -> Vec<Problem> {
+    let mut problems = vec![];
+    for (idx, line) in report_lines(input).enumerate() {
+        if line.len() != line_width {
+            problems.push(Problem::new(
+                idx + 1,
+                format!("line is {} wide, expected {}", line.len(), line_width),
+            ));
+        }
+        if let Some((col, chr)) = line
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !matches!(c, '0' | '1'))
+        {
+            problems.push(Problem::new(
+                idx + 1,
+                format!("invalid character {:?} at column {}", chr, col + 1),
+            ));
+        }
+    }
+
+    problems
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FollowMode {
+    // follow the most common bit
+    Most,
+    // follow the least common bit
+    Least,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StatResult {
+    One,
+    Zero,
+}
+
+/// Storage for the numbers of the report.
+pub trait Word: Copy + Ord + Debug + Binary {
+    /// builds a number from its bits, most significant bit first
+    fn from_bits(bits: impl Iterator<Item = bool>) -> Self;
+    /// whether the bit `idx` (counted from the least significant one) is set
+    fn bit(self, idx: usize) -> bool;
+    fn to_u64(self) -> u64;
+}
+
+macro_rules! impl_word {
+    ($($t:ty)*) => {
+        $(impl Word for $t {
+            fn from_bits(bits: impl Iterator<Item = bool>) -> Self {
+                bits.fold(0, |acc, bit| (acc << 1) | bit as $t)
+            }
+
+            #[inline(always)]
+            fn bit(self, idx: usize) -> bool {
+                (self >> idx) & 1 == 1
+            }
+
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+        })*
+    };
+}
+
+impl_word!(u32 u64);
+
+/// Which numbers a rating keeps when a column has as many ones as zeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    PreferOne,
+    PreferZero,
+    /// fail with `SearchErrorKind::Tie`
+    Error,
+}
+
+impl TieBreak {
+    /// The values of the `oxygen_ties` and `co2_ties` params, in the order of `ALL`.
+    pub const NAMES: &'static [&'static str] = &["prefer_zero", "prefer_one", "error"];
+    const ALL: [TieBreak; 3] = [TieBreak::PreferZero, TieBreak::PreferOne, TieBreak::Error];
+
+    /// The tie break for the index of a name in `NAMES`, which is what the params hold.
+    pub fn from_param(value: usize) -> TieBreak {
+        TieBreak::ALL[value]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieBreaks {
+    pub oxygen: TieBreak,
+    pub co2: TieBreak,
+}
+
+impl Default for TieBreaks {
+    /// the puzzle's rules
+    fn default() -> Self {
+        TieBreaks {
+            oxygen: TieBreak::PreferOne,
+            co2: TieBreak::PreferZero,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchErrorKind {
+    /// the report has no numbers
+    Empty,
+    /// `count` numbers were left, half of them with a one in `column` (counted from 1)
+    Tie { column: usize, count: usize },
+    /// `count` equal numbers were left after the last column
+    Duplicates { count: usize },
+}
+
+const OXYGEN: &str = "oxygen generator";
+const CO2: &str = "co2 scrubber";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchError {
+    /// "oxygen generator" or "co2 scrubber"
+    pub rating: &'static str,
+    pub kind: SearchErrorKind,
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} rating: ", self.rating)?;
+        match self.kind {
+            SearchErrorKind::Empty => write!(f, "the report is empty"),
+            SearchErrorKind::Tie { column, count } => write!(
+                f,
+                "{} ones and {} zeros in column {}",
+                count / 2,
+                count / 2,
+                column
+            ),
+            SearchErrorKind::Duplicates { count } => {
+                write!(f, "{} equal numbers are left after the last column", count)
+            }
+        }
+    }
+}
+
+/// `try_step_two` with the puzzle's tie-break rules, panics on empty or duplicated reports.
+pub fn step_two(input: &str, line_width: usize) -> StepTwoResult {
+    try_step_two(input, line_width, TieBreaks::default()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Picks the narrowest `Word` that fits `line_width` digits.
+pub fn try_step_two(
+    input: &str,
+    line_width: usize,
+    ties: TieBreaks,
+) -> Result<StepTwoResult, SearchError> {
+    assert!(line_width <= MAX_LINE_WIDTH);
+    if line_width <= 32 {
+        step_two_with::<u32>(input, line_width, ties)
+    } else {
+        step_two_with::<u64>(input, line_width, ties)
+    }
+}
+
+fn step_two_with<T: Word>(
+    input: &str,
+    line_width: usize,
+    ties: TieBreaks,
+) -> Result<StepTwoResult, SearchError> {
+    let numbers = parse_and_sort::<T>(input, line_width);
+
+    let oxygen =
+        search_value(&numbers, FollowMode::Most, ties.oxygen, line_width).map_err(|kind| {
+            SearchError {
+                rating: OXYGEN,
+                kind,
+            }
+        })?;
+    let co2 = search_value(&numbers, FollowMode::Least, ties.co2, line_width)
+        .map_err(|kind| SearchError { rating: CO2, kind })?;
+
+    Ok(StepTwoResult {
+        oxygen: oxygen.to_u64(),
+        co2: co2.to_u64(),
+    })
+}
+
+fn search_value<T: Word>(
+    nums: &[T],
+    mode: FollowMode,
+    ties: TieBreak,
+    line_width: usize,
+) -> Result<T, SearchErrorKind> {
+    let mut range = 0..nums.len();
+    for round in 0..line_width {
+        if range.len() <= 1 {
+            break;
+        }
+        let bit = line_width - 1 - round;
+        let numbers = &nums[range.clone()];
+        let most_common = most_common_digit(numbers, bit);
+        let split_point = find_split_point(numbers, bit, most_common);
+        if split_point == 0 || split_point == range.len() {
+            // every number has the same bit here, there is nothing to choose from
+            continue;
+        }
+
+        let keep = if split_point * 2 == range.len() {
+            match ties {
+                TieBreak::PreferOne => StatResult::One,
+                TieBreak::PreferZero => StatResult::Zero,
+                TieBreak::Error => {
+                    return Err(SearchErrorKind::Tie {
+                        column: round + 1,
+                        count: range.len(),
+                    })
+                }
+            }
+        } else {
+            match (most_common, mode) {
+                (most_common, FollowMode::Most) => most_common,
+                (StatResult::One, FollowMode::Least) => StatResult::Zero,
+                (StatResult::Zero, FollowMode::Least) => StatResult::One,
+            }
+        };
+        // the zeros come first
+        match keep {
+            StatResult::One => range.start += split_point,
+            StatResult::Zero => range.end = range.start + split_point,
+        };
+    }
+
+    match range.len() {
+        0 => Err(SearchErrorKind::Empty),
+        1 => Ok(nums[range.start]),
+        count => Err(SearchErrorKind::Duplicates { count }),
+    }
+}
+
+/// Same as `try_step_two`, but walks a `BitTrie` instead of searching the sorted numbers.
+pub fn try_step_two_trie(
+    input: &str,
+    line_width: usize,
+    ties: TieBreaks,
+) -> Result<StepTwoResult, SearchError> {
+    assert!(line_width <= MAX_LINE_WIDTH);
+    let trie = BitTrie::new(input, line_width);
+
+    let oxygen = trie
+        .rating(FollowMode::Most, ties.oxygen)
+        .map_err(|kind| SearchError {
+            rating: OXYGEN,
+            kind,
+        })?;
+    let co2 = trie
+        .rating(FollowMode::Least, ties.co2)
+        .map_err(|kind| SearchError { rating: CO2, kind })?;
+
+    Ok(StepTwoResult { oxygen, co2 })
+}
+
+/// `try_step_two_trie` with the puzzle's tie-break rules.
+pub fn step_two_trie(input: &str, line_width: usize) -> StepTwoResult {
+    try_step_two_trie(input, line_width, TieBreaks::default())
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// The lines of a report as a binary trie, most significant digit first.
+///
+/// Every node counts the lines below it, so a rating only compares the two children of a node
+/// instead of searching for the split point between them.
+pub struct BitTrie {
+    // the root is at index 0, which never is a child, so 0 also means "no child"
+    nodes: Vec<TrieNode>,
+    line_width: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TrieNode {
+    children: [u32; 2],
+    count: usize,
+}
+
+impl BitTrie {
+    pub fn new(input: &str, line_width: usize) -> BitTrie {
+        let mut nodes = vec![TrieNode::default()];
+        for line in report_lines(input) {
+            debug_assert!(line.len() == line_width);
+            let mut node = 0;
+            nodes[node].count += 1;
+            for digit in line.bytes() {
+                let bit = (digit == b'1') as usize;
+                if nodes[node].children[bit] == 0 {
+                    nodes[node].children[bit] = nodes.len() as u32;
+                    nodes.push(TrieNode::default());
+                }
+                node = nodes[node].children[bit] as usize;
+                nodes[node].count += 1;
+            }
+        }
+
+        BitTrie { nodes, line_width }
+    }
+
+    fn count(&self, node: u32) -> usize {
+        match node {
+            0 => 0,
+            node => self.nodes[node as usize].count,
+        }
+    }
+
+    /// Follows the most or least common digit down to a leaf, with the same rules and errors as
+    /// `search_value`.
+    fn rating(&self, mode: FollowMode, ties: TieBreak) -> Result<u64, SearchErrorKind> {
+        if self.nodes[0].count == 0 {
+            return Err(SearchErrorKind::Empty);
+        }
+
+        let mut node = 0;
+        let mut value = 0;
+        for column in 1..=self.line_width {
+            let [zero, one] = self.nodes[node].children;
+            let (zeros, ones) = (self.count(zero), self.count(one));
+            let keep_one = if zeros == 0 || ones == 0 {
+                // every number has the same bit here, there is nothing to choose from
+                ones > 0
+            } else if zeros == ones {
+                match ties {
+                    TieBreak::PreferOne => true,
+                    TieBreak::PreferZero => false,
+                    TieBreak::Error => {
+                        return Err(SearchErrorKind::Tie {
+                            column,
+                            count: zeros + ones,
+                        })
+                    }
+                }
+            } else {
+                match mode {
+                    FollowMode::Most => ones > zeros,
+                    FollowMode::Least => ones < zeros,
+                }
+            };
+            value = (value << 1) | keep_one as u64;
+            node = self.nodes[node].children[keep_one as usize] as usize;
+        }
+
+        match self.nodes[node].count {
+            1 => Ok(value),
+            count => Err(SearchErrorKind::Duplicates { count }),
+        }
+    }
+}
+
+fn find_split_point<T: Word>(numbers: &[T], bit: usize, most_common: StatResult) -> usize {
+    // when most_common is One, we know the upper half of the slice ([mid..end]) starts with one
+    // so we need to look for the first one in the lower half ([0..mid-1])
+    // when most_common is zero, we know the lower half of the slice starts with zero, so
+    // we need to look for the first zero in the upper half
+    let num_len = numbers.len();
+    let halfway_point = num_len - (num_len / 2);
+    let search_range = if most_common == StatResult::One {
+        0..halfway_point
+    } else {
+        halfway_point..num_len
+    };
+
+    // the numbers are sorted and agree on all higher bits, so zeros come before ones
+    search_range.start + numbers[search_range].partition_point(|num| !num.bit(bit))
+}
+
+#[inline(always)]
+fn center_of_range(range: &Range<usize>) -> usize {
+    let range_len = range.end - range.start;
+    if range_len & 0x01 == 0x01 {
+        range.start + (range_len / 2)
+    } else {
+        range.start + range_len - (range_len / 2)
+    }
+}
+
+fn most_common_digit<T: Word>(numbers: &[T], bit_for_round: usize) -> StatResult {
+    let center = center_of_range(&(0..numbers.len()));
+    let num_at_center = numbers[center];
+
+    if num_at_center.bit(bit_for_round) {
+        // 1 is most common, or as common as 0
+        StatResult::One
+    } else {
+        // 0 is most common
+        StatResult::Zero
+    }
+}
+
+fn parse_and_sort<T: Word>(input: &str, line_width: usize) -> Vec<T> {
+    let mut vec: Vec<_> = report_lines(input)
+        .map(|line| parse_binary_to_int(line, line_width))
+        .collect();
+    vec.sort_unstable();
+    vec
+}
+
+fn parse_binary_to_int<T: Word>(num: &str, line_width: usize) -> T {
+    debug_assert!(num.len() == line_width);
+    T::from_bits(num.bytes().map(|b| b == b'1'))
+}
+
+#[derive(Debug)]
+pub struct StepTwoResult {
+    oxygen: u64,
+    co2: u64,
+}
+
+#[derive(Debug)]
+pub struct StepOneResult {
+    gamma: u64,
+    epsilon: u64,
+}
+
+/// the lowest `line_width` bits set
+fn width_mask(line_width: usize) -> u64 {
+    u64::MAX >> (64 - line_width)
+}
+
+pub fn add_lines_naive(input: &str, _line_count: usize, line_width: usize) -> StepOneResult {
+    assert!(line_width <= MAX_LINE_WIDTH);
+    let mut one_count: [usize; MAX_LINE_WIDTH] = [0; MAX_LINE_WIDTH];
+    let mut zero_count: [usize; MAX_LINE_WIDTH] = [0; MAX_LINE_WIDTH];
+    for line in report_lines(input) {
+        debug_assert!(line.len() == line_width);
+        for (idx, chr) in line.chars().enumerate() {
+            match chr {
+                '0' => zero_count[idx] += 1,
+                '1' => one_count[idx] += 1,
+                _ => panic!("incorrect char: {}", chr),
+            };
+        }
+    }
+
+    let one_value = u64::from_bits((0..line_width).map(|idx| one_count[idx] > zero_count[idx]));
+    let zero_value = u64::from_bits((0..line_width).map(|idx| one_count[idx] <= zero_count[idx]));
+
+    StepOneResult {
+        gamma: one_value,
+        epsilon: zero_value,
+    }
+}
+
+/// Calls `$func::<W, LANES>(args)` with the runtime `$width` as the const `W`, and the number of
+/// simd lanes listed for it.
+macro_rules! dispatch_width {
+    ($width:expr, $func:ident $args:tt, $($lanes:literal: [$($w:literal)*]),*) => {
+        match $width {
+            $($($w => $func::<$w, $lanes> $args,)*)*
+            width => panic!("unsupported line width {}", width),
+        }
+    };
+}
+
+pub fn add_lines_simd(input: &str, line_count: usize, line_width: usize) -> StepOneResult {
+    add_lines_kernel(Kernel::detect(), input, line_count, line_width)
+}
+
+/// `add_lines_simd` with the given kernel instead of the best one for this cpu.
+pub fn add_lines_kernel(
+    kernel: Kernel,
+    input: &str,
+    line_count: usize,
+    line_width: usize,
+) -> StepOneResult {
+    let ones = count_ones_kernel(kernel, input.as_bytes(), line_count, line_width);
+    rates_from_counts(&ones[..line_width], line_count)
+}
+
+/// The instruction sets the column counting is compiled for.
+///
+/// All of them run the same `std::simd` code, the target features only decide which registers
+/// and instructions it is lowered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// whatever the compilation target supports
+    Portable,
+    Sse2,
+    Avx2,
+    /// needs both avx512f and avx512bw (for the u16 lanes)
+    Avx512,
+}
+
+impl Kernel {
+    pub const ALL: [Kernel; 4] = [Kernel::Portable, Kernel::Sse2, Kernel::Avx2, Kernel::Avx512];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Portable => "portable",
+            Kernel::Sse2 => "sse2",
+            Kernel::Avx2 => "avx2",
+            Kernel::Avx512 => "avx512",
+        }
+    }
+
+    /// Whether the cpu this is running on can run the kernel.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Kernel::Portable => true,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx512 => {
+                is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw")
+            }
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// The widest supported kernel.
+    pub fn detect() -> Kernel {
+        Kernel::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|kernel| kernel.is_supported())
+            .unwrap()
+    }
+}
+
+/// The number of ones in every column of the first `line_count` lines, columns past
+/// `line_width` stay at zero.
+pub fn count_ones_simd(
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    count_ones_kernel(Kernel::detect(), input, line_count, line_width)
+}
+
+/// `count_ones_simd` with the given kernel, panics if the cpu does not support it.
+pub fn count_ones_kernel(
+    kernel: Kernel,
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    assert!(kernel.is_supported(), "{:?} is not supported", kernel);
+    let row_len = Dimensions::row_len(input, line_width);
+    match kernel {
+        Kernel::Portable => count_ones_portable(input, line_count, line_width, row_len),
+        // safe, the cpu supports the features checked above
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Sse2 => unsafe { count_ones_sse2(input, line_count, line_width, row_len) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx2 => unsafe { count_ones_avx2(input, line_count, line_width, row_len) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx512 => unsafe { count_ones_avx512(input, line_count, line_width, row_len) },
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
+    }
+}
+
+// the kernels below inline all of `count_ones_portable` so it is compiled with their features
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn count_ones_sse2(
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+    row_len: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    count_ones_portable(input, line_count, line_width, row_len)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn count_ones_avx2(
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+    row_len: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    count_ones_portable(input, line_count, line_width, row_len)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw")]
+unsafe fn count_ones_avx512(
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+    row_len: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    count_ones_portable(input, line_count, line_width, row_len)
+}
+
+#[inline(always)]
+fn count_ones_portable(
+    input: &[u8],
+    line_count: usize,
+    line_width: usize,
+    row_len: usize,
+) -> [u64; MAX_LINE_WIDTH] {
+    dispatch_width!(
+        line_width,
+        count_ones_wide(input, line_count, row_len),
+        16: [1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16],
+        32: [17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32],
+        64: [33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+            49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64]
+    )
+}
+
+/// `count_ones_simd` for a line width known at compile time, so the per line copy is unrolled.
+#[inline(always)]
+fn count_ones_wide<const W: usize, const LANES: usize>(
+    input: &[u8],
+    line_count: usize,
+    row_len: usize,
+) -> [u64; MAX_LINE_WIDTH]
+where
+    LaneCount<LANES>: SupportedLaneCount,
+{
+    let mut ones = [0u64; MAX_LINE_WIDTH];
+    ones[..W].copy_from_slice(&count_ones_fixed::<W, LANES>(input, line_count, row_len)[..W]);
+    ones
+}
+
+// lines per rayon task, about 200 KiB of a 12 digit report, each task also flushes its u16
+// lanes every `FLUSH_INTERVAL` lines and returns a full set of column counts
+const PAR_CHUNK_LINES: usize = 1 << 14;
+
+/// Same as `add_lines_simd`, but counts chunks of whole lines in parallel and adds up their counts.
+pub fn add_lines_par(input: &str, line_count: usize, line_width: usize) -> StepOneResult {
+    let row_len = Dimensions::row_len(input.as_bytes(), line_width);
+    // the last line does not need a line ending
+    let input = &input.as_bytes()[..input.len().min(line_count * row_len)];
+    let ones = input
+        .par_chunks(PAR_CHUNK_LINES * row_len)
+        .map(|chunk| {
+            let lines = (chunk.len() + row_len - line_width) / row_len;
+            count_ones_simd(chunk, lines, line_width)
+        })
+        .reduce(
+            || [0u64; MAX_LINE_WIDTH],
+            |mut acc, ones| {
+                for (total, count) in acc.iter_mut().zip(ones) {
+                    *total += count;
+                }
+                acc
+            },
+        );
+    rates_from_counts(&ones[..line_width], line_count)
+}
+
+/// Lines that can be summed up as ascii digits (at most b'1' each) before a u16 lane overflows.
+const FLUSH_INTERVAL: usize = u16::MAX as usize / b'1' as usize;
+
+/// Counts the ones in every column of the first `line_count` lines, which start `row_len` bytes
+/// apart.
+///
+/// The digits are summed in u16 lanes, which are flushed into the u64 totals every
+/// `FLUSH_INTERVAL` lines.
+#[inline(always)]
+fn count_ones_fixed<const W: usize, const LANES: usize>(
+    input: &[u8],
+    line_count: usize,
+    row_len: usize,
+) -> [u64; LANES]
+where
+    LaneCount<LANES>: SupportedLaneCount,
+{
+    assert!(W <= LANES && W < row_len);
+    // the last line does not need a line ending
+    debug_assert!(line_count * row_len <= input.len() + row_len - W);
+    let mut totals = [0u64; LANES];
+    let mut first_row = 0;
+    while first_row < line_count {
+        let rows = FLUSH_INTERVAL.min(line_count - first_row);
+        let mut accumulator = Simd::<u16, LANES>::splat(0u16);
+        for rowidx in first_row..first_row + rows {
+            let row_start = rowidx * row_len;
+            let row_slice: &[u8; W] = input[row_start..row_start + W].try_into().unwrap();
+            // lanes past the end of the line stay at zero
+            let mut row = [0u16; LANES];
+            for lane in 0..W {
+                row[lane] = row_slice[lane] as u16;
+            }
+
+            accumulator += Simd::from_array(row);
+        }
+
+        // every line added at least '0' to each of its lanes
+        for (total, sum) in totals.iter_mut().zip(accumulator.to_array()).take(W) {
+            *total += (sum - b'0' as u16 * rows as u16) as u64;
+        }
+        first_row += rows;
+    }
+
+    totals
+}
+
+/// Gamma and epsilon from the number of ones per column, ties count as zero.
+pub fn rates_from_counts(ones: &[u64], line_count: usize) -> StepOneResult {
+    let gamma = u64::from_bits(ones.iter().map(|&count| count * 2 > line_count as u64));
+
+    StepOneResult {
+        gamma,
+        epsilon: !gamma & width_mask(ones.len()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_2021::xorshift::XorShift;
+
+    const LINE_WIDTH: usize = 12;
+    const NUM_LINES: usize = 1000;
+
+    #[test]
+    fn test_day03_part1() {
+        let res = add_lines_simd(INPUT, NUM_LINES, LINE_WIDTH);
+        assert_eq!(3912944, res.gamma as usize * res.epsilon as usize);
+    }
+
+    #[test]
+    fn test_day03_part2() {
+        let res = step_two(INPUT, LINE_WIDTH);
+        assert_eq!(4996233, res.oxygen as usize * res.co2 as usize);
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert!(check_input(INPUT, LINE_WIDTH, NUM_LINES).is_empty());
+
+        let problems = check_input("101010101010\n0101\n1010101010x0\n", LINE_WIDTH, 3);
+        assert_eq!(
+            vec![2, 3],
+            problems.iter().map(|p| p.line).collect::<Vec<_>>()
+        );
+
+        // the first line decides the detected width
+        let input = "0010\n111\n10110\n";
+        let dims = Dimensions::detect(input);
+        assert_eq!(
+            vec![2, 3],
+            check_lines(input, dims.line_width)
+                .iter()
+                .map(|p| p.line)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_add_simd() {
+        let input = "101010101010\n010101010101\n101010101010\n";
+
+        let exp_a = 0b101010101010;
+        let exp_b = 0b010101010101;
+
+        let res = add_lines_simd(input, 3, LINE_WIDTH);
+        assert_eq!(exp_a, res.gamma);
+        assert_eq!(exp_b, res.epsilon);
+    }
+
+    #[test]
+    fn test_naive() {
+        let input = "101010101010\n010101010101\n101010101010\n";
+
+        let exp_a = 0b101010101010;
+        let exp_b = 0b010101010101;
+
+        let res = add_lines_naive(input, 3, LINE_WIDTH);
+        assert_eq!(exp_a, res.gamma);
+        assert_eq!(exp_b, res.epsilon);
+    }
+
+    #[test]
+    fn test_step_two() {
+        let input = "001000010000\n111101111000\n101101011000\n101111011100\n101011010100\n011110111100\n001110011100\n111001110000\n100001000000\n110011100100\n000100001000\n010100101000\n";
+
+        let res = step_two(&input, LINE_WIDTH);
+
+        let exp_o = 0b101111011100;
+        let exp_c = 0b010100101000;
+        assert_eq!(exp_o, res.oxygen);
+        assert_eq!(exp_c, res.co2);
+    }
+
+    #[test]
+    fn test_detect_dimensions() {
+        let detected = |input| {
+            let dims = Dimensions::detect(input);
+            (dims.line_width, dims.num_lines)
+        };
+        assert_eq!((LINE_WIDTH, NUM_LINES), detected(INPUT));
+        assert_eq!((5, 3), detected("00100\n11110\n10110\n"));
+        // no final newline, or an extra empty line
+        assert_eq!((5, 3), detected("00100\n11110\n10110"));
+        assert_eq!((5, 3), detected("00100\n11110\n10110\n\n"));
+
+        // part 2 and `check_input` skip the extra empty line too
+        let input = "00100\n11110\n10110\n\n";
+        assert!(check_input(input, 5, 3).is_empty());
+        for step_two in [try_step_two, try_step_two_trie] {
+            let res = step_two(input, 5, TieBreaks::default()).unwrap();
+            assert_eq!((0b11110, 0b00100), (res.oxygen, res.co2));
+        }
+        assert_eq!((3, 1), detected("101"));
+        assert_eq!((0, 0), detected(""));
+        assert_eq!((5, 3), detected("00100\r\n11110\r\n10110\r\n"));
+        assert_eq!((5, 3), detected("00100\r\n11110\r\n10110"));
+        assert_eq!((1, 2), detected("1\r\n0\r\n"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        for (line_width, count, seed) in [(1, 99, 1), (5, 1000, 2), (12, 3 * PAR_CHUNK_LINES, 3)] {
+            let lines = random_report(line_width, count, seed);
+            let count = lines.len();
+            let expected = add_lines_naive(&lines.join("\n"), count, line_width);
+            for input in [lines.join("\r\n"), lines.join("\r\n") + "\r\n"] {
+                let dims = Dimensions::detect(&input);
+                assert_eq!((line_width, count), (dims.line_width, dims.num_lines));
+                assert!(check_input(&input, line_width, count).is_empty());
+                for res in [
+                    add_lines_simd(&input, count, line_width),
+                    add_lines_par(&input, count, line_width),
+                ] {
+                    assert_eq!((expected.gamma, expected.epsilon), (res.gamma, res.epsilon));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_matches_naive_for_every_width() {
+        for line_width in 1..=MAX_LINE_WIDTH {
+            let lines = random_report(line_width, 99, line_width as u64);
+            let input = lines.join("\n") + "\n";
+
+            let dims = Dimensions::detect(&input);
+            assert_eq!((line_width, lines.len()), (dims.line_width, dims.num_lines));
+            let simd = add_lines_simd(&input, dims.num_lines, dims.line_width);
+            let naive = add_lines_naive(&input, dims.num_lines, dims.line_width);
+            assert_eq!((naive.gamma, naive.epsilon), (simd.gamma, simd.epsilon));
+        }
+    }
+
+    #[test]
+    fn test_par_matches_simd() {
+        for (line_width, count, seed) in [
+            (12, 0, 1),
+            (12, 1000, 2),
+            (5, 20_000, 3),
+            (12, 100_000, 4),
+            (64, 3 * PAR_CHUNK_LINES + 7, 5),
+        ] {
+            let lines = random_report(line_width, count, seed);
+            for input in [lines.join("\n"), lines.join("\n") + "\n"] {
+                let simd = add_lines_simd(&input, lines.len(), line_width);
+                let par = add_lines_par(&input, lines.len(), line_width);
+                assert_eq!((simd.gamma, simd.epsilon), (par.gamma, par.epsilon));
+            }
+        }
+
+        let input = INPUT.repeat(100);
+        let res = add_lines_par(&input, 100_000, 12);
+        assert_eq!(3912944, res.gamma * res.epsilon);
+    }
+
+    #[test]
+    fn test_kernels_match_naive() {
+        assert!(Kernel::detect().is_supported());
+        for kernel in Kernel::ALL.iter().copied().filter(|k| k.is_supported()) {
+            for line_width in 1..=MAX_LINE_WIDTH {
+                // enough lines to flush the lanes a few times
+                let lines = random_report(line_width, 3 * FLUSH_INTERVAL + 5, line_width as u64);
+                let input = lines.join("\n");
+                let res = add_lines_kernel(kernel, &input, lines.len(), line_width);
+                let naive = add_lines_naive(&input, lines.len(), line_width);
+                assert_eq!(
+                    (naive.gamma, naive.epsilon),
+                    (res.gamma, res.epsilon),
+                    "{} at width {}",
+                    kernel.name(),
+                    line_width
+                );
+            }
+        }
+    }
+
+    /// up to `count` distinct random lines of `line_width` digits
+    fn random_report(line_width: usize, count: usize, seed: u64) -> Vec<String> {
+        let mut numbers: Vec<u64> = XorShift::new(seed)
+            .take(count)
+            .map(|n| n & width_mask(line_width))
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        // back into a random order
+        numbers.sort_unstable_by_key(|n| n.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+
+        numbers
+            .iter()
+            .map(|n| format!("{:0width$b}", n, width = line_width))
+            .collect()
+    }
+
+    /// the rating as described by the puzzle: keep filtering by one column at a time
+    fn rating_by_filtering(lines: &[String], most_common: bool) -> u64 {
+        let mut keep: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let mut col = 0;
+        while keep.len() > 1 {
+            let ones = keep
+                .iter()
+                .filter(|line| line.as_bytes()[col] == b'1')
+                .count();
+            // a bit nobody has can't be followed, all numbers are kept
+            if ones > 0 && ones < keep.len() {
+                let want_one = (ones * 2 >= keep.len()) == most_common;
+                keep.retain(|line| (line.as_bytes()[col] == b'1') == want_one);
+            }
+            col += 1;
+        }
+        u64::from_str_radix(keep[0], 2).unwrap()
+    }
+
+    #[test]
+    fn test_boundary_widths() {
+        // the edges of the u32 storage, the simd lane groups and u64
+        for line_width in [1, 2, 15, 16, 17, 31, 32, 33, 48, 63, 64] {
+            let lines = random_report(line_width, 200, 7);
+            let input = lines.join("\n") + "\n";
+
+            let res = step_two(&input, line_width);
+            assert_eq!(
+                (
+                    rating_by_filtering(&lines, true),
+                    rating_by_filtering(&lines, false)
+                ),
+                (res.oxygen, res.co2),
+                "line width {}",
+                line_width
+            );
+
+            let res = add_lines_simd(&input, lines.len(), line_width);
+            assert_eq!(width_mask(line_width), res.gamma ^ res.epsilon);
+        }
+
+        // a split with one number on the least common side
+        let res = step_two("0000\n0001\n0010\n", 4);
+        assert_eq!((0b0001, 0b0010), (res.oxygen, res.co2));
+
+        let all_ones = "1".repeat(64) + "\n";
+        let res = add_lines_simd(&all_ones, 1, 64);
+        assert_eq!((u64::MAX, 0), (res.gamma, res.epsilon));
+        let res = step_two(&(all_ones + &"1".repeat(63) + "0\n"), 64);
+        assert_eq!((u64::MAX, u64::MAX - 1), (res.oxygen, res.co2));
+    }
+
+    #[test]
+    fn test_million_lines() {
+        // far more lines than a u16 lane can count
+        let ones = "111111111111\n".repeat(1_000_000);
+        let res = add_lines_simd(&ones, 1_000_000, 12);
+        assert_eq!((0xfff, 0), (res.gamma, res.epsilon));
+
+        // one more one than zeros in every other column, ties in the others
+        let mut input = "101010101010\n010101010100\n".repeat(500_000);
+        input.push_str("101010101010\n");
+        let res = add_lines_simd(&input, 1_000_001, 12);
+        assert_eq!((0b101010101010, 0b010101010101), (res.gamma, res.epsilon));
+        let res = add_lines_naive(&input, 1_000_001, 12);
+        assert_eq!((0b101010101010, 0b010101010101), (res.gamma, res.epsilon));
+
+        let lines = random_report(20, 1_000_000, 3);
+        let input = lines.join("\n");
+        let simd = add_lines_simd(&input, lines.len(), 20);
+        let naive = add_lines_naive(&input, lines.len(), 20);
+        assert_eq!((naive.gamma, naive.epsilon), (simd.gamma, simd.epsilon));
+    }
+
+    #[test]
+    fn test_example_with_line_width_param() {
+        let input =
+            "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n";
+
+        let dims = Dimensions::detect(input);
+        assert_eq!((5, 12), (dims.line_width, dims.num_lines));
+        let res = add_lines_simd(input, 12, 5);
+        assert_eq!((22, 9), (res.gamma, res.epsilon));
+        let res = add_lines_naive(input, 12, 5);
+        assert_eq!((22, 9), (res.gamma, res.epsilon));
+
+        let res = step_two(input, 5);
+        assert_eq!((23, 10), (res.oxygen, res.co2));
+    }
+
+    #[test]
+    fn test_tie_breaks() {
+        let input =
+            "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n";
+        let ties = |oxygen, co2| TieBreaks { oxygen, co2 };
+
+        let res = try_step_two(input, 5, TieBreaks::default()).unwrap();
+        assert_eq!((23, 10), (res.oxygen, res.co2));
+        // the oxygen rating ends with a tie between 10110 and 10111, co2 with 01010 and 01111
+        let res = try_step_two(input, 5, ties(TieBreak::PreferZero, TieBreak::PreferOne)).unwrap();
+        assert_eq!((22, 15), (res.oxygen, res.co2));
+
+        let err = try_step_two(input, 5, ties(TieBreak::Error, TieBreak::Error)).unwrap_err();
+        assert_eq!(
+            SearchError {
+                rating: "oxygen generator",
+                kind: SearchErrorKind::Tie {
+                    column: 5,
+                    count: 2
+                }
+            },
+            err
+        );
+        let err = try_step_two(input, 5, ties(TieBreak::PreferOne, TieBreak::Error)).unwrap_err();
+        assert_eq!(
+            "co2 scrubber rating: 1 ones and 1 zeros in column 3",
+            err.to_string()
+        );
+
+        // only the columns that actually split the numbers can tie
+        let res = try_step_two(
+            "0110\n0111\n",
+            4,
+            ties(TieBreak::PreferZero, TieBreak::PreferZero),
+        );
+        assert_eq!((6, 6), res.map(|res| (res.oxygen, res.co2)).unwrap());
+    }
+
+    #[test]
+    fn test_trie_matches_sort() {
+        let input =
+            "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n";
+        let res = step_two_trie(input, 5);
+        assert_eq!((23, 10), (res.oxygen, res.co2));
+        let res = step_two_trie(INPUT, LINE_WIDTH);
+        assert_eq!(4996233, res.oxygen * res.co2);
+
+        let policies = [TieBreak::PreferOne, TieBreak::PreferZero, TieBreak::Error];
+        let to_tuple = |res: StepTwoResult| (res.oxygen, res.co2);
+        for (seed, line_width) in (1..=MAX_LINE_WIDTH).step_by(3).enumerate() {
+            for &count in [0, 1, 2, 7, 100, 1000].iter() {
+                let mut lines = random_report(line_width, count, seed as u64);
+                // every third input repeats some of its lines
+                if seed % 3 == 0 {
+                    lines.extend(lines.clone().into_iter().step_by(4));
+                }
+                let input = lines.join("\n");
+
+                for &oxygen in policies.iter() {
+                    for &co2 in policies.iter() {
+                        let ties = TieBreaks { oxygen, co2 };
+                        assert_eq!(
+                            try_step_two(&input, line_width, ties).map(to_tuple),
+                            try_step_two_trie(&input, line_width, ties).map(to_tuple),
+                            "{} lines of width {} with {:?}",
+                            lines.len(),
+                            line_width,
+                            ties
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_errors() {
+        let res = try_step_two("101\n", 3, TieBreaks::default()).unwrap();
+        assert_eq!((5, 5), (res.oxygen, res.co2));
+
+        let err = try_step_two("", 3, TieBreaks::default()).unwrap_err();
+        assert_eq!(SearchErrorKind::Empty, err.kind);
+        let err = try_step_two_trie("", 3, TieBreaks::default()).unwrap_err();
+        assert_eq!(SearchErrorKind::Empty, err.kind);
+
+        let err = try_step_two("101\n011\n101\n", 3, TieBreaks::default()).unwrap_err();
+        assert_eq!(
+            SearchError {
+                rating: "oxygen generator",
+                kind: SearchErrorKind::Duplicates { count: 2 }
+            },
+            err
+        );
+        assert_eq!(
+            "oxygen generator rating: 2 equal numbers are left after the last column",
+            err.to_string()
+        );
+    }
+}
+
+/*
+00 0b000100001000  0
+01 0b001000010000  1
+02 0b001110011100  2
+03 0b010100101000  3
+04 0b011110111100  4
+05 0b100001000000 05
+06 0b101011010100 16
+07 0b101101011000 2
+08 0b101111011100 3
+09 0b110011100100 4
+10 0b111001110000 5
+11 0b111101111000 6
+*/
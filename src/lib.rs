@@ -1,3 +1,12 @@
 #![feature(portable_simd)]
-pub mod bin;
-pub use bin::day03;
+// lets the day modules refer to `aoc_2021::...` the same way the binaries do
+extern crate self as aoc_2021;
+
+pub mod cache;
+pub mod check;
+pub mod cli;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod params;
+pub mod xorshift;
@@ -0,0 +1,264 @@
+use crate::cli::Args;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+const DEFAULT_CONFIG_FILE: &str = "aoc.toml";
+
+/// A tunable value of a day, declared together with its default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: usize,
+    pub help: &'static str,
+    /// if not empty, the value is given as one of these names and stands for its index
+    pub choices: &'static [&'static str],
+}
+
+impl Param {
+    fn describe(&self, value: usize) -> String {
+        match self.choices {
+            [] => value.to_string(),
+            choices => choices[value].to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParamError {
+    Unknown { name: String, source: String },
+    InvalidValue { name: String, value: String },
+    Syntax { line: usize, message: String },
+    Io(String),
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamError::Unknown { name, source } => {
+                write!(f, "unknown parameter {} (from {})", name, source)
+            }
+            ParamError::InvalidValue { name, value } => {
+                write!(f, "invalid value for {}: {:?}", name, value)
+            }
+            ParamError::Syntax { line, message } => {
+                write!(f, "config line {}: {}", line, message)
+            }
+            ParamError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// The resolved parameter values of a day.
+///
+/// Values are taken from the declared defaults, overridden by the day's section in the config
+/// file, overridden by `--param name=value` on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Params {
+    values: Vec<(Param, usize)>,
+}
+
+impl Params {
+    pub fn defaults(declared: &[Param]) -> Params {
+        Params {
+            values: declared.iter().map(|&p| (p, p.default)).collect(),
+        }
+    }
+
+    /// Resolves the parameters for `day`, exits with a message if they are invalid.
+    pub fn from_args(day: &str, declared: &[Param], args: &Args) -> Params {
+        Params::resolve(day, declared, args).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            for param in declared {
+                eprintln!(
+                    "  {} (default {}): {}",
+                    param.name,
+                    param.describe(param.default),
+                    param.help
+                );
+            }
+            std::process::exit(2);
+        })
+    }
+
+    pub fn resolve(day: &str, declared: &[Param], args: &Args) -> Result<Params, ParamError> {
+        let mut params = Params::defaults(declared);
+
+        let config = match &args.config {
+            Some(path) => Some(read_config(path)?),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Some(read_config(Path::new(DEFAULT_CONFIG_FILE))?)
+            }
+            None => None,
+        };
+        if let Some(config) = config {
+            for (name, value) in parse_config(&config, day)? {
+                params.set(&name, &value, "config")?;
+            }
+        }
+
+        for (name, value) in &args.params {
+            params.set(name, value, "command line")?;
+        }
+
+        Ok(params)
+    }
+
+    /// Returns the value of a declared parameter.
+    pub fn get(&self, name: &str) -> usize {
+        self.values
+            .iter()
+            .find(|(p, _)| p.name == name)
+            .map(|&(_, v)| v)
+            .unwrap_or_else(|| panic!("parameter {} was never declared", name))
+    }
+
+    fn set(&mut self, name: &str, value: &str, source: &str) -> Result<(), ParamError> {
+        let slot = self
+            .values
+            .iter_mut()
+            .find(|(p, _)| p.name == name)
+            .ok_or_else(|| ParamError::Unknown {
+                name: name.to_string(),
+                source: source.to_string(),
+            })?;
+
+        let parsed = match slot.0.choices {
+            [] => value.trim().parse().ok(),
+            choices => choices.iter().position(|&choice| choice == value.trim()),
+        };
+        slot.1 = parsed.ok_or_else(|| ParamError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        })?;
+        Ok(())
+    }
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, (param, value)) in self.values.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", param.name, param.describe(*value))?;
+        }
+        Ok(())
+    }
+}
+
+fn read_config(path: &Path) -> Result<String, ParamError> {
+    std::fs::read_to_string(path)
+        .map_err(|err| ParamError::Io(format!("could not read {}: {}", path.display(), err)))
+}
+
+/// Parses the `[day]` section of a (very small subset of) TOML: sections, `key = value` pairs
+/// and `#` comments.
+fn parse_config(config: &str, day: &str) -> Result<Vec<(String, String)>, ParamError> {
+    let mut in_section = false;
+    let mut pairs = vec![];
+    for (idx, line) in config.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[') {
+            let section = section
+                .strip_suffix(']')
+                .ok_or_else(|| ParamError::Syntax {
+                    line: idx + 1,
+                    message: "unterminated section header".to_string(),
+                })?;
+            in_section = section.trim() == day;
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| ParamError::Syntax {
+            line: idx + 1,
+            message: format!("expected `key = value`, got {:?}", line),
+        })?;
+        if in_section {
+            pairs.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DECLARED: &[Param] = &[
+        Param {
+            name: "days",
+            default: 80,
+            help: "days to simulate",
+            choices: &[],
+        },
+        Param {
+            name: "width",
+            default: 3,
+            help: "window width",
+            choices: &[],
+        },
+    ];
+
+    #[test]
+    fn test_parse_config_section() {
+        let config = "# tuning\n[day01]\nwidth = 5\n\n[day06]\ndays = 18 # sample\n";
+        assert_eq!(
+            vec![("days".to_string(), "18".to_string())],
+            parse_config(config, "day06").unwrap()
+        );
+        assert!(matches!(
+            parse_config("[day06\n", "day06"),
+            Err(ParamError::Syntax { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_params_override_order() {
+        let mut params = Params::defaults(DECLARED);
+        assert_eq!(80, params.get("days"));
+        assert_eq!("days=80,width=3", params.to_string());
+
+        for (name, value) in parse_config("[day06]\ndays = 18\nwidth = 2\n", "day06").unwrap() {
+            params.set(&name, &value, "config").unwrap();
+        }
+        params.set("days", "256", "command line").unwrap();
+        assert_eq!(256, params.get("days"));
+        assert_eq!(2, params.get("width"));
+
+        assert!(matches!(
+            params.set("nope", "1", "command line"),
+            Err(ParamError::Unknown { .. })
+        ));
+        assert!(matches!(
+            params.set("days", "-1", "command line"),
+            Err(ParamError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_param_choices() {
+        let declared = &[Param {
+            name: "ties",
+            default: 1,
+            help: "what to do on a tie",
+            choices: &["low", "high", "error"],
+        }];
+        let mut params = Params::defaults(declared);
+        assert_eq!("ties=high", params.to_string());
+
+        params.set("ties", " error", "command line").unwrap();
+        assert_eq!(2, params.get("ties"));
+        // only the names, an index is easy to mix up
+        for value in ["2", "medium"] {
+            assert!(matches!(
+                params.set("ties", value, "command line"),
+                Err(ParamError::InvalidValue { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/// Deterministic pseudo random numbers for tests and benchmarks, no need for a rand dependency.
+///
+/// xorshift64, so a sequence does not repeat a number before its period of 2^64 - 1.
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // a zero state would only ever produce zeros
+        XorShift {
+            state: (0x2545f4914f6cdd1d ^ seed).max(1),
+        }
+    }
+}
+
+impl Iterator for XorShift {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        Some(self.state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_seeds() {
+        let first = |seed| XorShift::new(seed).take(3).collect::<Vec<_>>();
+        assert_eq!(first(7), first(7));
+        assert_ne!(first(7), first(8));
+        assert!(first(0x2545f4914f6cdd1d).iter().all(|&n| n != 0));
+    }
+}